@@ -0,0 +1,15 @@
+fn main() {
+    // Written to OUT_DIR, a capi test checks it matches include/glyphs_generator.h
+    #[cfg(feature = "capi")]
+    {
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        println!("cargo:rerun-if-changed=src/capi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml"))
+            .expect("Unable to read cbindgen.toml");
+        cbindgen::generate_with_config(&crate_dir, config)
+            .expect("Unable to generate C header")
+            .write_to_file(format!("{out_dir}/glyphs_generator.h"));
+    }
+}
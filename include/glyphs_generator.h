@@ -0,0 +1,52 @@
+#ifndef GLYPHS_GENERATOR_H
+#define GLYPHS_GENERATOR_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef struct GlyphGenerator GlyphGenerator;
+
+/**
+ * Message of the last error on this thread, or null. Valid until the next call failing
+ * on this thread; not to be freed.
+ */
+const char *glyphs_last_error(void);
+
+/**
+ * Generator for the parameters JSON, or null on error.
+ *
+ * # Safety
+ *
+ * `parameters_json` must be null or a valid NUL-terminated string.
+ */
+struct GlyphGenerator *glyphs_generator_new(const char *parameters_json);
+
+/**
+ * # Safety
+ *
+ * `generator` must be null or returned by `glyphs_generator_new` and not freed yet.
+ */
+void glyphs_generator_free(struct GlyphGenerator *generator);
+
+/**
+ * Glyphs JSON generated from the computable JSON, as `compute` returns it, or null on
+ * error. The caller frees it with `glyphs_string_free`.
+ *
+ * # Safety
+ *
+ * `generator` must be returned by `glyphs_generator_new` and not freed yet,
+ * `computable_json` must be null or a valid NUL-terminated string.
+ */
+char *glyphs_generator_generate(const struct GlyphGenerator *generator,
+                                const char *computable_json);
+
+/**
+ * # Safety
+ *
+ * `s` must be null or a string returned by this library and not freed yet.
+ */
+void glyphs_string_free(char *s);
+
+#endif  /* GLYPHS_GENERATOR_H */
@@ -0,0 +1,73 @@
+export type StrokeKind = { "type": "line" } | { "type": "arc", bulge: number, } | { "type": "quadratic", cx: number, cy: number, } | { "type": "cubic", cx0: number, cy0: number, cx1: number, cy1: number, };
+
+export type Stroke = { x0: number, y0: number, x1: number, y1: number, kind?: StrokeKind, };
+
+/**
+ * A stroke given either by the indices of its two anchors, as `[a, b]`, or by its geometry.
+ */
+export type StrokeRef = [number, number] | Stroke;
+
+export type Glyph = { strokes: Array<Stroke>, };
+
+export type Parameters = { 
+/**
+ * Format version, see `schema::PARAMETERS_VERSION`.
+ */
+version?: number, parent_strokes: Array<Stroke>, intersection_matrix: Array<Array<number>>, transformation_matrix: Array<Array<number>>, };
+
+/**
+ * Way two strokes meet.
+ */
+export type Contact = "endpoint" | "t_junction" | "crossing" | "overlap";
+
+/**
+ * Rules generated glyphs must follow, strokes given as in the request.
+ */
+export type Constraints = { 
+/**
+ * Strokes every glyph must contain.
+ */
+required?: Array<StrokeRef>, 
+/**
+ * Strokes no glyph may contain.
+ */
+forbidden?: Array<StrokeRef>, 
+/**
+ * Stroke sets no glyph may contain entirely.
+ */
+forbidden_sets?: Array<Array<StrokeRef>>, 
+/**
+ * Stroke groups a glyph may use at most one stroke of.
+ */
+exclusive_groups?: Array<Array<StrokeRef>>, 
+/**
+ * Contacts through which strokes connect, any contact when empty.
+ */
+linking_contacts?: Array<Contact>, 
+/**
+ * Contacts no two strokes of a glyph may make.
+ */
+forbidden_contacts?: Array<Contact>, };
+
+/**
+ * When two glyphs count as the same glyph.
+ */
+export type Normalization = "none" | "overlaps" | "planar";
+
+/**
+ * A glyph kept by a normalization, with the glyphs dropped as drawing the same figure.
+ */
+export type NormalizedGlyph = { glyph: Glyph, 
+/**
+ * Strokes the glyph draws under the normalization.
+ */
+drawing: Array<Stroke>, collapsed: Array<Glyph>, };
+
+/**
+ * Generation request, the first stroke being the seed.
+ */
+export type Computable = { 
+/**
+ * Format version, see `schema::COMPUTABLE_VERSION`.
+ */
+version?: number, strokes: Array<StrokeRef>, constraints?: Constraints, normalization?: Normalization, };
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+
+use crate::complexity::ComplexityWeights;
+use crate::distance::Metric;
+use crate::generate::GlyphGenerator;
+use crate::glyph::Glyph;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// Farthest-point selection.
+    Greedy,
+    /// Greedy selection refined by swapping glyphs of the closest pair.
+    LocalSearch { iterations: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlphabetOptions {
+    pub size: usize,
+    pub metric: Metric,
+    pub strategy: Strategy,
+    pub min_strokes: usize,
+    pub max_strokes: Option<usize>,
+    /// Minimum number of glyphs invariant under at least one transformation.
+    pub min_symmetric: usize,
+    /// Minimum number of glyphs invariant under no transformation.
+    pub min_asymmetric: usize,
+    /// Upper bound on the complexity score of a glyph.
+    pub max_complexity: Option<f64>,
+    pub complexity_weights: ComplexityWeights,
+}
+
+impl Default for AlphabetOptions {
+    fn default() -> Self {
+        AlphabetOptions {
+            size: 30,
+            metric: Metric::SymmetricStrokeDifference,
+            strategy: Strategy::Greedy,
+            min_strokes: 0,
+            max_strokes: None,
+            min_symmetric: 0,
+            min_asymmetric: 0,
+            max_complexity: None,
+            complexity_weights: ComplexityWeights::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alphabet {
+    /// Indices of the selected glyphs in the input set.
+    pub indices: Vec<usize>,
+    pub min_distance: f64,
+}
+
+struct Selector<'a> {
+    generator: &'a GlyphGenerator,
+    glyphs: &'a [Glyph],
+    metric: Metric,
+    cache: HashMap<(usize, usize), f64>,
+}
+
+impl Selector<'_> {
+    fn distance(&mut self, i: usize, j: usize) -> f64 {
+        let key = (i.min(j), i.max(j));
+        if let Some(&d) = self.cache.get(&key) {
+            return d;
+        }
+        let d = self
+            .generator
+            .distance(self.metric, &self.glyphs[i], &self.glyphs[j]);
+        self.cache.insert(key, d);
+        d
+    }
+
+    fn distance_to_set(&mut self, i: usize, selected: &[usize]) -> f64 {
+        selected
+            .iter()
+            .map(|&j| self.distance(i, j))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    fn closest_pair(&mut self, selected: &[usize]) -> Option<(usize, usize, f64)> {
+        let mut best: Option<(usize, usize, f64)> = None;
+        for a in 0..selected.len() {
+            for b in a + 1..selected.len() {
+                let d = self.distance(selected[a], selected[b]);
+                if best.is_none_or(|(_, _, min)| d < min) {
+                    best = Some((a, b, d));
+                }
+            }
+        }
+        best
+    }
+}
+
+impl GlyphGenerator {
+    pub fn is_symmetric(&self, glyph: &Glyph) -> bool {
+        self.orbit(glyph).iter().skip(1).any(|g| g == glyph)
+    }
+
+    /// Picks `options.size` glyphs maximizing the minimum pairwise distance, or fewer if the
+    /// constraints leave fewer candidates.
+    pub fn select_alphabet(&self, glyphs: &[Glyph], options: &AlphabetOptions) -> Alphabet {
+        let candidates: Vec<usize> = (0..glyphs.len())
+            .filter(|&i| {
+                let glyph = &glyphs[i];
+                let nb_strokes = glyph.strokes.len();
+                nb_strokes >= options.min_strokes
+                    && options.max_strokes.is_none_or(|max| nb_strokes <= max)
+                    && options.max_complexity.is_none_or(|max| {
+                        self.complexity(glyph).score(&options.complexity_weights) <= max
+                    })
+            })
+            .collect();
+        let mut symmetric = vec![false; glyphs.len()];
+        for &i in &candidates {
+            symmetric[i] = self.is_symmetric(&glyphs[i]);
+        }
+
+        // Whether adding a glyph of the given kind still leaves room for the required mix
+        let fits = |selected: &[usize], is_symmetric: bool| -> bool {
+            let nb_symmetric = selected.iter().filter(|&&i| symmetric[i]).count();
+            let nb_asymmetric = selected.len() - nb_symmetric;
+            let missing_symmetric = options
+                .min_symmetric
+                .saturating_sub(nb_symmetric + is_symmetric as usize);
+            let missing_asymmetric = options
+                .min_asymmetric
+                .saturating_sub(nb_asymmetric + !is_symmetric as usize);
+            selected.len() + 1 + missing_symmetric + missing_asymmetric <= options.size
+        };
+
+        let mut selector = Selector {
+            generator: self,
+            glyphs,
+            metric: options.metric,
+            cache: HashMap::new(),
+        };
+        let mut selected: Vec<usize> = Vec::new();
+        while selected.len() < options.size {
+            let mut best: Option<(usize, f64)> = None;
+            for &i in &candidates {
+                if selected.contains(&i) || !fits(&selected, symmetric[i]) {
+                    continue;
+                }
+                let d = selector.distance_to_set(i, &selected);
+                if best.is_none_or(|(_, max)| d > max) {
+                    best = Some((i, d));
+                }
+            }
+            match best {
+                Some((i, _)) => selected.push(i),
+                None => break,
+            }
+        }
+
+        if let Strategy::LocalSearch { iterations } = options.strategy {
+            for _ in 0..iterations {
+                let Some((a, b, current)) = selector.closest_pair(&selected) else {
+                    break;
+                };
+                let mut best: Option<(usize, usize, f64)> = None;
+                for position in [a, b] {
+                    let mut rest = selected.clone();
+                    rest.remove(position);
+                    let rest_distance = selector
+                        .closest_pair(&rest)
+                        .map_or(f64::INFINITY, |(_, _, d)| d);
+                    for &i in &candidates {
+                        if selected.contains(&i) || !fits(&rest, symmetric[i]) {
+                            continue;
+                        }
+                        let d = selector.distance_to_set(i, &rest).min(rest_distance);
+                        if d > current && best.is_none_or(|(_, _, max)| d > max) {
+                            best = Some((position, i, d));
+                        }
+                    }
+                }
+                match best {
+                    Some((position, i, _)) => selected[position] = i,
+                    None => break,
+                }
+            }
+        }
+
+        let min_distance = selector
+            .closest_pair(&selected)
+            .map_or(f64::INFINITY, |(_, _, d)| d);
+        Alphabet {
+            indices: selected,
+            min_distance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::setup;
+
+    #[test]
+    fn test_select_alphabet() {
+        let context = setup();
+        let generator = context.basic;
+        let strokes = generator.parameters.parent_strokes.clone();
+        let glyphs = generator.generate(&strokes, &strokes[0]);
+
+        let greedy = generator.select_alphabet(
+            &glyphs,
+            &AlphabetOptions {
+                size: 4,
+                ..AlphabetOptions::default()
+            },
+        );
+        assert_eq!(greedy.indices.len(), 4);
+        assert!(greedy.min_distance > 0.0);
+
+        let local = generator.select_alphabet(
+            &glyphs,
+            &AlphabetOptions {
+                size: 4,
+                strategy: Strategy::LocalSearch { iterations: 10 },
+                ..AlphabetOptions::default()
+            },
+        );
+        assert!(local.min_distance >= greedy.min_distance);
+    }
+
+    #[test]
+    fn test_select_alphabet_constraints() {
+        let context = setup();
+        let generator = context.basic;
+        let strokes = generator.parameters.parent_strokes.clone();
+        let glyphs = generator.generate(&strokes, &strokes[0]);
+
+        let alphabet = generator.select_alphabet(
+            &glyphs,
+            &AlphabetOptions {
+                size: 5,
+                metric: Metric::Hausdorff,
+                min_strokes: 2,
+                max_strokes: Some(4),
+                min_asymmetric: 2,
+                ..AlphabetOptions::default()
+            },
+        );
+        assert_eq!(alphabet.indices.len(), 5);
+        for &i in &alphabet.indices {
+            assert!((2..=4).contains(&glyphs[i].strokes.len()));
+        }
+        let nb_asymmetric = alphabet
+            .indices
+            .iter()
+            .filter(|&&i| !generator.is_symmetric(&glyphs[i]))
+            .count();
+        assert!(nb_asymmetric >= 2);
+    }
+}
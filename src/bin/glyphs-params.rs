@@ -0,0 +1,75 @@
+use std::fs;
+use std::process::ExitCode;
+
+use glyphs_generator::generate::GlyphGenerator;
+use glyphs_generator::parameters::Parameters;
+
+const USAGE: &str = "Usage:
+  glyphs-params inspect <parameters.json>
+  glyphs-params diff <old.json> <new.json>
+  glyphs-params regenerate <parameters.json>
+  glyphs-params check <parameters.json>...
+  glyphs-params svg <parameters.json> [stroke]";
+
+fn load(path: &str) -> Result<Parameters, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    Parameters::from_json(&json).map_err(|e| format!("{path}: {e}"))
+}
+
+fn run(args: &[String]) -> Result<bool, String> {
+    match args {
+        [command, path] if command == "inspect" => {
+            print!("{}", load(path)?.summary());
+            Ok(true)
+        }
+        [command, old, new] if command == "diff" => {
+            let diff = load(old)?.diff(&load(new)?);
+            print!("{diff}");
+            Ok(diff.is_empty())
+        }
+        [command, path] if command == "regenerate" => {
+            println!(
+                "{}",
+                serde_json::to_string(&load(path)?.regenerate()).unwrap()
+            );
+            Ok(true)
+        }
+        [command, paths @ ..] if command == "check" && !paths.is_empty() => {
+            let mut clean = true;
+            for path in paths {
+                let drift = load(path)?.drift();
+                print!("{path}: {drift}");
+                clean &= drift.is_empty();
+            }
+            Ok(clean)
+        }
+        [command, path, selected @ ..] if command == "svg" && selected.len() <= 1 => {
+            let generator = GlyphGenerator::new(load(path)?);
+            let selected = match selected.first() {
+                Some(index) => {
+                    let index: usize = index.parse().map_err(|_| USAGE.to_string())?;
+                    if index >= generator.parameters.parent_strokes.len() {
+                        return Err(format!("{path}: no stroke {index}"));
+                    }
+                    Some(index)
+                }
+                None => None,
+            };
+            println!("{}", generator.render_parameters(selected, 500.0));
+            Ok(true)
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use std::fmt;
+
+use crate::generate::GlyphGenerator;
+use crate::glyph::{set_bits, Glyph, InternalGlyph, MAX_PARENT_STROKES};
+use crate::parameters::Parameters;
+use miniz_oxide::deflate::compress_to_vec;
+use miniz_oxide::inflate::decompress_to_vec_with_limit;
+
+const MAGIC: &[u8; 4] = b"GLYB";
+const VERSION: u8 = 1;
+const COMPRESSED: u8 = 1;
+const COMPRESSION_LEVEL: u8 = 6;
+/// Largest payload a compressed archive may inflate to.
+const MAX_PAYLOAD: usize = 1 << 28;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    InvalidMagic,
+    UnsupportedVersion(u8),
+    Truncated,
+    Decompression,
+    Parameters(String),
+    InvalidWidth(usize),
+    InvalidStroke(usize),
+    TrailingBytes(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::InvalidMagic => write!(f, "Not a glyph archive"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported glyph archive version {version}")
+            }
+            DecodeError::Truncated => write!(f, "Truncated glyph archive"),
+            DecodeError::Decompression => write!(f, "Could not decompress glyph archive"),
+            DecodeError::Parameters(error) => write!(f, "Invalid archive parameters: {error}"),
+            DecodeError::InvalidWidth(width) => {
+                write!(f, "Invalid glyph archive width of {width} bytes per glyph")
+            }
+            DecodeError::InvalidStroke(index) => {
+                write!(f, "Glyph archive references missing parent stroke {index}")
+            }
+            DecodeError::TrailingBytes(count) => {
+                write!(f, "{count} unexpected bytes after the glyph archive")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Glyphs stored as bitmasks over the parent strokes of their parameters.
+///
+/// Encoded as the magic `GLYB`, a version byte and a flags byte, followed by the payload,
+/// deflated when the compressed flag is set: the parameters as length-prefixed JSON, the
+/// glyph count, the number of bytes per glyph and the little-endian glyph bitmasks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphArchive {
+    pub parameters: Parameters,
+    pub identifiers: Vec<u64>,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < n {
+            return Err(DecodeError::Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+impl GlyphArchive {
+    fn width(&self) -> usize {
+        self.parameters.parent_strokes.len().div_ceil(8)
+    }
+
+    pub fn glyphs(&self) -> Vec<Glyph> {
+        self.identifiers
+            .iter()
+            .map(|&identifier| Glyph {
+                strokes: InternalGlyph::from_identifier(identifier)
+                    .strokes
+                    .iter()
+                    .map(|s| self.parameters.parent_strokes[s.index])
+                    .collect(),
+            })
+            .collect()
+    }
+
+    pub fn to_bytes(&self, compress: bool) -> Vec<u8> {
+        let parameters = serde_json::to_vec(&self.parameters).unwrap();
+        let width = self.width();
+        let mut payload = Vec::with_capacity(9 + parameters.len() + width * self.identifiers.len());
+        payload.extend((parameters.len() as u32).to_le_bytes());
+        payload.extend(&parameters);
+        payload.extend((self.identifiers.len() as u32).to_le_bytes());
+        payload.push(width as u8);
+        for identifier in &self.identifiers {
+            payload.extend(&identifier.to_le_bytes()[..width]);
+        }
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        if compress {
+            bytes.push(COMPRESSED);
+            bytes.extend(compress_to_vec(&payload, COMPRESSION_LEVEL));
+        } else {
+            bytes.push(0);
+            bytes.extend(payload);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader { bytes };
+        if reader.take(4).map_err(|_| DecodeError::InvalidMagic)? != MAGIC {
+            return Err(DecodeError::InvalidMagic);
+        }
+        let version = reader.take(1)?[0];
+        if version != VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        let flags = reader.take(1)?[0];
+        let inflated;
+        let payload = if flags & COMPRESSED != 0 {
+            inflated = decompress_to_vec_with_limit(reader.bytes, MAX_PAYLOAD)
+                .map_err(|_| DecodeError::Decompression)?;
+            &inflated[..]
+        } else {
+            reader.bytes
+        };
+
+        let mut reader = Reader { bytes: payload };
+        let length = reader.u32()? as usize;
+        let parameters = serde_json::from_slice(reader.take(length)?)
+            .map_err(|e| e.to_string())
+            .and_then(|value| Parameters::from_value(value).map_err(|e| e.to_string()))
+            .map_err(DecodeError::Parameters)?;
+        let count = reader.u32()? as usize;
+        let width = reader.take(1)?[0] as usize;
+        let strokes = parameters.parent_strokes.len();
+        // Without parent strokes there is nothing to bound the glyph count by
+        if strokes > MAX_PARENT_STROKES || width != strokes.div_ceil(8) || (width == 0 && count > 0)
+        {
+            return Err(DecodeError::InvalidWidth(width));
+        }
+        let data = reader.take(count.checked_mul(width).ok_or(DecodeError::Truncated)?)?;
+        let identifiers = data
+            .chunks_exact(width.max(1))
+            .map(|chunk| {
+                let mut buffer = [0u8; 8];
+                buffer[..width].copy_from_slice(chunk);
+                let identifier = u64::from_le_bytes(buffer);
+                match set_bits(identifier).find(|&index| index >= strokes) {
+                    Some(index) => Err(DecodeError::InvalidStroke(index)),
+                    None => Ok(identifier),
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        if !reader.bytes.is_empty() {
+            return Err(DecodeError::TrailingBytes(reader.bytes.len()));
+        }
+
+        Ok(GlyphArchive {
+            parameters,
+            identifiers,
+        })
+    }
+}
+
+impl GlyphGenerator {
+    pub fn archive(&self, glyphs: &[Glyph]) -> GlyphArchive {
+        GlyphArchive {
+            parameters: self.parameters.clone(),
+            identifiers: glyphs
+                .iter()
+                .map(|glyph| self.from_glyph(glyph).identifier)
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive() -> GlyphArchive {
+        let parameters: Parameters =
+            serde_json::from_str(include_str!("../tests/parameters_4ap.json")).unwrap();
+        GlyphArchive {
+            parameters,
+            identifiers: vec![0b000001, 0b100011, 0b111111],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let archive = archive();
+        for compress in [false, true] {
+            let bytes = archive.to_bytes(compress);
+            assert_eq!(GlyphArchive::from_bytes(&bytes).unwrap(), archive);
+        }
+        // One byte per glyph for six parent strokes
+        let parameters = serde_json::to_vec(&archive.parameters).unwrap();
+        assert_eq!(
+            archive.to_bytes(false).len(),
+            6 + 4 + parameters.len() + 5 + 3
+        );
+
+        let glyphs = archive.glyphs();
+        assert_eq!(glyphs[1].strokes.len(), 3);
+        assert_eq!(glyphs[2].strokes, archive.parameters.parent_strokes);
+    }
+
+    #[test]
+    fn test_invalid_bytes() {
+        let bytes = archive().to_bytes(false);
+        assert_eq!(
+            GlyphArchive::from_bytes(b"JSON"),
+            Err(DecodeError::InvalidMagic)
+        );
+        assert_eq!(
+            GlyphArchive::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::Truncated)
+        );
+        let mut future = bytes.clone();
+        future[4] = VERSION + 1;
+        assert_eq!(
+            GlyphArchive::from_bytes(&future),
+            Err(DecodeError::UnsupportedVersion(VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn test_untrusted_headers() {
+        let archive = archive();
+        let bytes = archive.to_bytes(false);
+        for length in 0..bytes.len() {
+            assert!(GlyphArchive::from_bytes(&bytes[..length]).is_err());
+        }
+        let compressed = archive.to_bytes(true);
+        assert!(GlyphArchive::from_bytes(&compressed[..compressed.len() / 2]).is_err());
+
+        // The width byte follows the glyph count, just before the three one-byte glyphs
+        let width = bytes.len() - 4;
+        let mut wide = bytes.clone();
+        wide[width] = 2;
+        assert_eq!(
+            GlyphArchive::from_bytes(&wide),
+            Err(DecodeError::InvalidWidth(2))
+        );
+        let mut huge = bytes.clone();
+        huge[width - 4..width].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(GlyphArchive::from_bytes(&huge), Err(DecodeError::Truncated));
+
+        let mut missing = bytes.clone();
+        missing[width + 2] = 0b1000_0011;
+        assert_eq!(
+            GlyphArchive::from_bytes(&missing),
+            Err(DecodeError::InvalidStroke(7))
+        );
+    }
+
+    #[test]
+    fn test_trailing_bytes() {
+        let archive = archive();
+        let mut raw = archive.to_bytes(false);
+        raw.push(0);
+        assert_eq!(
+            GlyphArchive::from_bytes(&raw),
+            Err(DecodeError::TrailingBytes(1))
+        );
+
+        let mut payload = archive.to_bytes(false)[6..].to_vec();
+        payload.extend([0, 0]);
+        let mut inflated = archive.to_bytes(true)[..6].to_vec();
+        inflated.extend(compress_to_vec(&payload, COMPRESSION_LEVEL));
+        assert_eq!(
+            GlyphArchive::from_bytes(&inflated),
+            Err(DecodeError::TrailingBytes(2))
+        );
+    }
+
+    #[test]
+    fn test_versioned_parameters() {
+        let mut archive = archive();
+        archive.parameters.version += 1;
+        let bytes = archive.to_bytes(false);
+        assert!(matches!(
+            GlyphArchive::from_bytes(&bytes),
+            Err(DecodeError::Parameters(error)) if error.contains("newer")
+        ));
+    }
+}
@@ -0,0 +1,160 @@
+//! C interface. Strings are NUL-terminated UTF-8; strings returned by the library are
+//! released with `glyphs_string_free`.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use crate::generate::GlyphGenerator;
+use crate::parameters::Parameters;
+use crate::Computable;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|error| *error.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, recording its error or panic message for `glyphs_last_error`.
+fn guard<T>(f: impl FnOnce() -> Result<T, String>) -> Option<T> {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Some(value),
+        Ok(Err(message)) => {
+            set_error(message);
+            None
+        }
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "Unknown error".to_string());
+            set_error(message);
+            None
+        }
+    }
+}
+
+unsafe fn read_str<'a>(s: *const c_char) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err("Null string".to_string());
+    }
+    CStr::from_ptr(s).to_str().map_err(|e| e.to_string())
+}
+
+/// Message of the last error on this thread, or null. Valid until the next call failing
+/// on this thread; not to be freed.
+#[no_mangle]
+pub extern "C" fn glyphs_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| {
+        error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Generator for the parameters JSON, or null on error.
+///
+/// # Safety
+///
+/// `parameters_json` must be null or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn glyphs_generator_new(
+    parameters_json: *const c_char,
+) -> *mut GlyphGenerator {
+    guard(|| {
+        let parameters =
+            Parameters::from_json(read_str(parameters_json)?).map_err(|e| e.to_string())?;
+        Ok(Box::into_raw(Box::new(GlyphGenerator::new(parameters))))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// # Safety
+///
+/// `generator` must be null or returned by `glyphs_generator_new` and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn glyphs_generator_free(generator: *mut GlyphGenerator) {
+    if !generator.is_null() {
+        drop(Box::from_raw(generator));
+    }
+}
+
+/// Glyphs JSON generated from the computable JSON, as `compute` returns it, or null on
+/// error. The caller frees it with `glyphs_string_free`.
+///
+/// # Safety
+///
+/// `generator` must be returned by `glyphs_generator_new` and not freed yet,
+/// `computable_json` must be null or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn glyphs_generator_generate(
+    generator: *const GlyphGenerator,
+    computable_json: *const c_char,
+) -> *mut c_char {
+    guard(|| {
+        let generator = generator.as_ref().ok_or("Null generator")?;
+        let computable =
+            Computable::from_json(read_str(computable_json)?).map_err(|e| e.to_string())?;
+        let glyphs = computable.generate(generator)?;
+        let json = serde_json::to_string(&glyphs).unwrap();
+        Ok(CString::new(json).unwrap().into_raw())
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// # Safety
+///
+/// `s` must be null or a string returned by this library and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn glyphs_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_committed_header() {
+        // Copy the generated header over the committed one after changing the interface
+        assert_eq!(
+            include_str!(concat!(env!("OUT_DIR"), "/glyphs_generator.h")),
+            include_str!("../include/glyphs_generator.h")
+        );
+    }
+
+    #[test]
+    fn test_capi() {
+        let parameters = CString::new(include_str!("../tests/parameters_4ap.json")).unwrap();
+        let computable = CString::new("{\"strokes\":[[0,1],[0,2],[1,3]]}").unwrap();
+        let invalid = CString::new("{\"strokes\":[[0,0]]}").unwrap();
+        unsafe {
+            let generator = glyphs_generator_new(parameters.as_ptr());
+            assert!(!generator.is_null());
+
+            let json = glyphs_generator_generate(generator, computable.as_ptr());
+            assert!(!json.is_null());
+            let glyphs: Vec<crate::glyph::Glyph> =
+                serde_json::from_str(CStr::from_ptr(json).to_str().unwrap()).unwrap();
+            assert_eq!(glyphs[0].strokes.len(), 1);
+            glyphs_string_free(json);
+
+            // Unknown anchor pair
+            assert!(glyphs_generator_generate(generator, invalid.as_ptr()).is_null());
+            let error = CStr::from_ptr(glyphs_last_error()).to_str().unwrap();
+            assert!(error.contains("Could not find stroke"));
+
+            glyphs_generator_free(generator);
+            assert!(glyphs_generator_new(computable.as_ptr()).is_null());
+            assert!(!glyphs_last_error().is_null());
+        }
+    }
+}
@@ -0,0 +1,398 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::constraints::ConstraintMasks;
+use crate::generate::{Expansion, Generation, GlyphGenerator};
+use crate::glyph::{Glyph, InternalGlyph, MAX_PARENT_STROKES};
+use crate::parameters::Parameters;
+use crate::stroke::Stroke;
+use serde::{Deserialize, Serialize};
+
+pub const CHECKPOINT_VERSION: u32 = 2;
+
+/// Time between checkpoints saved in the middle of a level by `generate_resumable`.
+pub const DEFAULT_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(io::Error),
+    Format(String),
+    UnsupportedVersion(u32),
+    /// The checkpoint was written for other parameters, constraints, strokes or seed.
+    Incompatible,
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CheckpointError::Io(error) => write!(f, "Checkpoint I/O error: {error}"),
+            CheckpointError::Format(error) => write!(f, "Invalid checkpoint: {error}"),
+            CheckpointError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported checkpoint version {version}")
+            }
+            CheckpointError::Incompatible => {
+                write!(f, "Checkpoint belongs to another generation")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
+
+impl From<io::Error> for CheckpointError {
+    fn from(error: io::Error) -> Self {
+        CheckpointError::Io(error)
+    }
+}
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// FNV-1a hash of the parameters JSON, stable across builds and platforms.
+pub fn parameters_fingerprint(parameters: &Parameters) -> u64 {
+    fnv1a(0xcbf29ce484222325, &serde_json::to_vec(parameters).unwrap())
+}
+
+/// Parameters fingerprint extended with the constraint masks JSON.
+pub fn generation_fingerprint(parameters: &Parameters, masks: &ConstraintMasks) -> u64 {
+    fnv1a(
+        parameters_fingerprint(parameters),
+        &serde_json::to_vec(masks).unwrap(),
+    )
+}
+
+/// Expansion of the level following the last completed one, as far as it went.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialLevel {
+    /// Number of glyphs of the last level expanded.
+    pub expanded: usize,
+    /// Classes of the next level found by then, in the order found.
+    pub found: Vec<u64>,
+}
+
+/// Completed generation levels, as glyph identifiers, of a generation from `seed` over
+/// `strokes`, both given as parent stroke indices, and the progress of the next level.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub version: u32,
+    pub fingerprint: u64,
+    pub strokes: Vec<usize>,
+    pub seed: usize,
+    pub levels: Vec<Vec<u64>>,
+    pub partial: Option<PartialLevel>,
+}
+
+impl Checkpoint {
+    pub fn load(path: &Path) -> Result<Self, CheckpointError> {
+        let json = fs::read_to_string(path)?;
+        let checkpoint: Checkpoint =
+            serde_json::from_str(&json).map_err(|e| CheckpointError::Format(e.to_string()))?;
+        if checkpoint.version != CHECKPOINT_VERSION {
+            return Err(CheckpointError::UnsupportedVersion(checkpoint.version));
+        }
+        checkpoint.validate().map_err(CheckpointError::Format)?;
+        Ok(checkpoint)
+    }
+
+    /// Checks the levels fit the strokes and the glyphs use only them, so that once the
+    /// strokes match the resumed generation every identifier is within its parent strokes.
+    fn validate(&self) -> Result<(), String> {
+        let strokes = self.strokes.iter().try_fold(0u64, |acc, &index| {
+            if index >= MAX_PARENT_STROKES {
+                return Err(format!("stroke {index} does not fit glyph identifiers"));
+            }
+            Ok(acc | 1 << index)
+        })?;
+        let Some(last) = self.levels.last() else {
+            return Err("no level".to_string());
+        };
+        if self.levels.len() > self.strokes.len().max(1) {
+            return Err(format!(
+                "{} levels for {} strokes",
+                self.levels.len(),
+                self.strokes.len()
+            ));
+        }
+        let found = self.partial.iter().flat_map(|partial| &partial.found);
+        if let Some(identifier) = self
+            .levels
+            .iter()
+            .flatten()
+            .chain(found)
+            .find(|&&identifier| identifier & !strokes != 0)
+        {
+            return Err(format!(
+                "glyph {identifier:#b} uses strokes outside the generation"
+            ));
+        }
+        match &self.partial {
+            Some(partial) if partial.expanded > last.len() => Err(format!(
+                "{} glyphs expanded in a level of {}",
+                partial.expanded,
+                last.len()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes next to `path` first, so an interruption never leaves a partial checkpoint.
+    pub fn save(&self, path: &Path) -> Result<(), CheckpointError> {
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        let temporary = PathBuf::from(temporary);
+        fs::write(&temporary, serde_json::to_string(self).unwrap())?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+}
+
+impl GlyphGenerator {
+    /// Same glyphs as `generate`, saving a checkpoint to `path` after every level and every
+    /// `DEFAULT_SAVE_INTERVAL` within a level, and resuming from the checkpoint already
+    /// there, if any.
+    pub fn generate_resumable(
+        &self,
+        strokes: &[Stroke],
+        seed: &Stroke,
+        path: &Path,
+    ) -> Result<Vec<Glyph>, CheckpointError> {
+        self.generate_resumable_constrained(
+            strokes,
+            seed,
+            &ConstraintMasks::default(),
+            path,
+            DEFAULT_SAVE_INTERVAL,
+        )
+    }
+
+    /// Same glyphs as `generate_constrained`, saving a checkpoint to `path` after every level
+    /// and every `interval` within a level, and resuming from the checkpoint already there,
+    /// if any.
+    pub fn generate_resumable_constrained(
+        &self,
+        strokes: &[Stroke],
+        seed: &Stroke,
+        masks: &ConstraintMasks,
+        path: &Path,
+        interval: Duration,
+    ) -> Result<Vec<Glyph>, CheckpointError> {
+        let index = |stroke: &Stroke| self.from_stroke(stroke).strokes[0].index;
+        let identifiers = |level: &[InternalGlyph]| level.iter().map(|g| g.identifier).collect();
+        let glyphs = |identifiers: &[u64]| {
+            identifiers
+                .iter()
+                .map(|&identifier| InternalGlyph::from_identifier(identifier))
+                .collect()
+        };
+        let mut generation = Generation::new(self, strokes, seed, masks.clone());
+        let mut checkpoint = Checkpoint {
+            version: CHECKPOINT_VERSION,
+            fingerprint: generation_fingerprint(&self.parameters, masks),
+            strokes: strokes.iter().map(index).collect(),
+            seed: index(seed),
+            levels: vec![identifiers(generation.level())],
+            partial: None,
+        };
+        if path.exists() {
+            let mut saved = Checkpoint::load(path)?;
+            if (saved.fingerprint, &saved.strokes, saved.seed)
+                != (checkpoint.fingerprint, &checkpoint.strokes, checkpoint.seed)
+            {
+                return Err(CheckpointError::Incompatible);
+            }
+            let expansion = match saved.partial.take() {
+                Some(partial) => Expansion::resume(partial.expanded, glyphs(&partial.found)),
+                None => Expansion::default(),
+            };
+            generation = Generation::resume(
+                self,
+                strokes,
+                masks.clone(),
+                saved.levels.len() - 1,
+                glyphs(saved.levels.last().unwrap()),
+                expansion,
+            );
+            checkpoint = saved;
+        }
+
+        let mut saved_at = Instant::now();
+        let flow = generation.run(
+            self,
+            |_, _, _, _| {},
+            |generation, completed| {
+                let expansion = generation.expansion();
+                if completed {
+                    checkpoint.levels.push(identifiers(generation.level()));
+                } else if saved_at.elapsed() >= interval && !expansion.is_done(generation.level()) {
+                    checkpoint.partial = Some(PartialLevel {
+                        expanded: expansion.expanded(),
+                        found: expansion.found_identifiers(),
+                    });
+                } else {
+                    return ControlFlow::Continue(());
+                }
+                let saved = checkpoint.save(path);
+                checkpoint.partial = None;
+                saved_at = Instant::now();
+                match saved {
+                    Ok(()) => ControlFlow::Continue(()),
+                    Err(error) => ControlFlow::Break(error),
+                }
+            },
+        );
+        if let ControlFlow::Break(error) = flow {
+            return Err(error);
+        }
+
+        Ok(checkpoint
+            .levels
+            .iter()
+            .flatten()
+            .filter_map(|&identifier| {
+                self.admissible(&InternalGlyph::from_identifier(identifier), masks)
+            })
+            .map(|g| self.to_glyph(&g))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::Constraints;
+    use crate::stroke::StrokeRef;
+    use crate::tests::setup;
+
+    #[test]
+    fn test_generate_resumable() {
+        let context = setup();
+        let generator = context.basic;
+        let strokes = generator.parameters.parent_strokes.clone();
+        let path =
+            std::env::temp_dir().join(format!("glyphs-checkpoint-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let expected = generator.generate(&strokes, &strokes[0]);
+        let glyphs = generator
+            .generate_resumable(&strokes, &strokes[0], &path)
+            .unwrap();
+        assert_eq!(glyphs, expected);
+
+        // Resume after an interruption following the second level
+        let mut checkpoint = Checkpoint::load(&path).unwrap();
+        assert_eq!(checkpoint.levels.len(), strokes.len());
+        checkpoint.levels.truncate(2);
+        checkpoint.save(&path).unwrap();
+        let resumed = generator
+            .generate_resumable(&strokes, &strokes[0], &path)
+            .unwrap();
+        assert_eq!(resumed, expected);
+
+        let other = context.advanced;
+        let other_strokes = other.parameters.parent_strokes.clone();
+        assert!(matches!(
+            other.generate_resumable(&other_strokes[..3], &other_strokes[0], &path),
+            Err(CheckpointError::Incompatible)
+        ));
+        assert!(matches!(
+            generator.generate_resumable(&strokes, &strokes[1], &path),
+            Err(CheckpointError::Incompatible)
+        ));
+
+        // Glyphs using strokes outside the generation are rejected
+        let mut foreign = checkpoint.clone();
+        foreign.levels[1].push(1 << strokes.len());
+        foreign.save(&path).unwrap();
+        assert!(matches!(
+            Checkpoint::load(&path),
+            Err(CheckpointError::Format(_))
+        ));
+        let mut partial = checkpoint.clone();
+        partial.partial = Some(PartialLevel {
+            expanded: 0,
+            found: vec![1 << strokes.len()],
+        });
+        partial.save(&path).unwrap();
+        assert!(matches!(
+            Checkpoint::load(&path),
+            Err(CheckpointError::Format(_))
+        ));
+
+        checkpoint.version += 1;
+        checkpoint.save(&path).unwrap();
+        assert!(matches!(
+            Checkpoint::load(&path),
+            Err(CheckpointError::UnsupportedVersion(_))
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_generate_resumable_within_level() {
+        let context = setup();
+        let generator = context.basic;
+        let strokes = generator.parameters.parent_strokes.clone();
+        let path = std::env::temp_dir().join(format!(
+            "glyphs-partial-checkpoint-{}.tmp",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        let constraints = Constraints {
+            forbidden: vec![StrokeRef::Stroke(strokes[5])],
+            ..Default::default()
+        };
+
+        let masks = generator.constraint_masks(&constraints).unwrap();
+        let expected = generator
+            .generate_constrained(&strokes, &strokes[0], &constraints)
+            .unwrap();
+        let glyphs = generator
+            .generate_resumable_constrained(&strokes, &strokes[0], &masks, &path, Duration::ZERO)
+            .unwrap();
+        assert_eq!(glyphs, expected);
+        assert!(!path.with_extension("tmp.tmp").exists());
+
+        // Resume after an interruption in the middle of the third level
+        let mut checkpoint = Checkpoint::load(&path).unwrap();
+        assert_eq!(checkpoint.partial, None);
+        checkpoint.levels.truncate(3);
+        let level: Vec<InternalGlyph> = checkpoint.levels[2]
+            .iter()
+            .map(|&identifier| InternalGlyph::from_identifier(identifier))
+            .collect();
+        assert!(level.len() > 1);
+        let strokes_internal: Vec<InternalGlyph> =
+            strokes.iter().map(|s| generator.from_stroke(s)).collect();
+        let mut expansion = Expansion::default();
+        expansion.advance(&generator, &level, &strokes_internal, &masks);
+        checkpoint.partial = Some(PartialLevel {
+            expanded: expansion.expanded(),
+            found: expansion.found_identifiers(),
+        });
+        checkpoint.save(&path).unwrap();
+        let resumed = generator
+            .generate_resumable_constrained(
+                &strokes,
+                &strokes[0],
+                &masks,
+                &path,
+                Duration::from_secs(3600),
+            )
+            .unwrap();
+        assert_eq!(resumed, expected);
+
+        // The constraints are part of the fingerprint
+        assert!(matches!(
+            generator.generate_resumable(&strokes, &strokes[0], &path),
+            Err(CheckpointError::Incompatible)
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+}
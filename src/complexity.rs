@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+
+use crate::generate::GlyphGenerator;
+use crate::geometry::{end, length, start, stroke_intersections, BoundingBox, PlanarGraph};
+use crate::glyph::Glyph;
+use crate::stroke::Stroke;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Complexity {
+    pub strokes: usize,
+    pub ink_length: f64,
+    /// Points where two strokes cross away from their endpoints.
+    pub crossings: usize,
+    /// Free ends of the drawing, i.e. vertices of degree one.
+    pub endpoints: usize,
+    pub enclosed_regions: usize,
+    /// Area of the glyph bounding box relative to the area of the frame.
+    pub coverage: f64,
+    /// Minimum number of times the pen has to be lifted to draw the glyph.
+    pub pen_lifts: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComplexityWeights {
+    pub strokes: f64,
+    pub ink_length: f64,
+    pub crossings: f64,
+    pub endpoints: f64,
+    pub enclosed_regions: f64,
+    pub coverage: f64,
+    pub pen_lifts: f64,
+}
+
+impl Default for ComplexityWeights {
+    fn default() -> Self {
+        ComplexityWeights {
+            strokes: 1.0,
+            ink_length: 0.5,
+            crossings: 1.0,
+            endpoints: 0.5,
+            enclosed_regions: 1.0,
+            coverage: 0.0,
+            pen_lifts: 2.0,
+        }
+    }
+}
+
+impl Complexity {
+    pub fn score(&self, weights: &ComplexityWeights) -> f64 {
+        weights.strokes * self.strokes as f64
+            + weights.ink_length * self.ink_length
+            + weights.crossings * self.crossings as f64
+            + weights.endpoints * self.endpoints as f64
+            + weights.enclosed_regions * self.enclosed_regions as f64
+            + weights.coverage * self.coverage
+            + weights.pen_lifts * self.pen_lifts as f64
+    }
+}
+
+fn crossings(a: &Stroke, b: &Stroke) -> usize {
+    let ends = [start(a), end(a), start(b), end(b)];
+    stroke_intersections(a, b)
+        .iter()
+        .filter(|point| ends.iter().all(|p| p.key() != point.key()))
+        .count()
+}
+
+impl Glyph {
+    /// Complexity features of the glyph, coverage being measured against `frame`.
+    pub fn complexity(&self, frame: &BoundingBox) -> Complexity {
+        let graph = PlanarGraph::from_strokes(&self.strokes);
+        let degrees: Vec<usize> = (0..graph.vertices.len()).map(|i| graph.degree(i)).collect();
+
+        let crossings = self
+            .strokes
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                self.strokes[i + 1..]
+                    .iter()
+                    .map(|b| crossings(a, b))
+                    .sum::<usize>()
+            })
+            .sum();
+
+        // Each connected component needs one pen path per pair of odd vertices, at least one
+        let mut odd_vertices: HashMap<usize, usize> = HashMap::new();
+        for (vertex, label) in graph.components().into_iter().enumerate() {
+            *odd_vertices.entry(label).or_insert(0) += degrees[vertex] % 2;
+        }
+        let pen_paths: usize = odd_vertices.values().map(|&odd| (odd / 2).max(1)).sum();
+
+        let coverage = match BoundingBox::from_strokes(&self.strokes) {
+            Some(bbox) if frame.area() > 0.0 => bbox.area() / frame.area(),
+            _ => 0.0,
+        };
+
+        Complexity {
+            strokes: self.strokes.len(),
+            ink_length: self.strokes.iter().map(length).sum(),
+            crossings,
+            endpoints: degrees.iter().filter(|&&d| d == 1).count(),
+            enclosed_regions: graph.enclosed_regions(),
+            coverage,
+            pen_lifts: pen_paths.saturating_sub(1),
+        }
+    }
+}
+
+impl GlyphGenerator {
+    /// Complexity of a glyph, with coverage measured against the parent strokes.
+    pub fn complexity(&self, glyph: &Glyph) -> Complexity {
+        let frame = BoundingBox::from_strokes(&self.parameters.parent_strokes)
+            .expect("Parameters should contain parent strokes");
+        glyph.complexity(&frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabet::AlphabetOptions;
+    use crate::geometry::Point;
+    use crate::tests::setup;
+
+    fn glyph(strokes: &[(f64, f64, f64, f64)]) -> Glyph {
+        Glyph {
+            strokes: strokes
+                .iter()
+                .map(|&(x0, y0, x1, y1)| Stroke::line(x0, y0, x1, y1))
+                .collect(),
+        }
+    }
+
+    fn frame() -> BoundingBox {
+        BoundingBox {
+            min: Point::new(-1.0, -1.0),
+            max: Point::new(1.0, 1.0),
+        }
+    }
+
+    #[test]
+    fn test_complexity_cross() {
+        let complexity =
+            glyph(&[(-1.0, -1.0, 1.0, 1.0), (-1.0, 1.0, 1.0, -1.0)]).complexity(&frame());
+        assert_eq!(complexity.strokes, 2);
+        assert!((complexity.ink_length - 4.0 * 2f64.sqrt()).abs() < 1e-9);
+        assert_eq!(complexity.crossings, 1);
+        assert_eq!(complexity.endpoints, 4);
+        assert_eq!(complexity.enclosed_regions, 0);
+        assert_eq!(complexity.coverage, 1.0);
+        assert_eq!(complexity.pen_lifts, 1);
+    }
+
+    #[test]
+    fn test_complexity_closed_box() {
+        let complexity = glyph(&[
+            (-1.0, -1.0, -1.0, 1.0),
+            (-1.0, 1.0, 1.0, 1.0),
+            (1.0, 1.0, 1.0, -1.0),
+            (1.0, -1.0, -1.0, -1.0),
+        ])
+        .complexity(&frame());
+        assert_eq!(complexity.crossings, 0);
+        assert_eq!(complexity.endpoints, 0);
+        assert_eq!(complexity.enclosed_regions, 1);
+        assert_eq!(complexity.pen_lifts, 0);
+    }
+
+    #[test]
+    fn test_score() {
+        let simple = glyph(&[(-1.0, -1.0, -1.0, 1.0)]).complexity(&frame());
+        let complex = glyph(&[(-1.0, -1.0, 1.0, 1.0), (-1.0, 1.0, 1.0, -1.0)]).complexity(&frame());
+        let weights = ComplexityWeights::default();
+        assert!(simple.score(&weights) < complex.score(&weights));
+        assert_eq!(
+            simple.score(&ComplexityWeights {
+                strokes: 1.0,
+                ink_length: 0.0,
+                crossings: 0.0,
+                endpoints: 0.0,
+                enclosed_regions: 0.0,
+                coverage: 0.0,
+                pen_lifts: 0.0,
+            }),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_sort_by_complexity() {
+        let context = setup();
+        let generator = context.basic;
+        let strokes = generator.parameters.parent_strokes.clone();
+        let mut glyphs = generator.generate(&strokes, &strokes[0]);
+
+        let weights = ComplexityWeights::default();
+        glyphs.sort_by(|a, b| {
+            let a = generator.complexity(a).score(&weights);
+            let b = generator.complexity(b).score(&weights);
+            a.total_cmp(&b)
+        });
+        assert_eq!(glyphs[0].strokes.len(), 1);
+        assert_eq!(glyphs.last().unwrap().strokes.len(), 6);
+
+        let alphabet = generator.select_alphabet(
+            &glyphs,
+            &AlphabetOptions {
+                max_complexity: Some(generator.complexity(&glyphs[5]).score(&weights)),
+                ..AlphabetOptions::default()
+            },
+        );
+        for &i in &alphabet.indices {
+            assert!(
+                generator.complexity(&glyphs[i]).score(&weights)
+                    <= generator.complexity(&glyphs[5]).score(&weights)
+            );
+        }
+    }
+}
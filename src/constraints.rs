@@ -0,0 +1,314 @@
+use crate::contact::Contact;
+use crate::generate::{GlyphGenerator, ResolveError};
+use crate::glyph::{set_bits, Glyph, InternalGlyph, MAX_PARENT_STROKES};
+use crate::stroke::{Stroke, StrokeRef};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Rules generated glyphs must follow, strokes given as in the request.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(default)]
+pub struct Constraints {
+    /// Strokes every glyph must contain.
+    #[cfg_attr(test, ts(as = "Option<Vec<StrokeRef>>", optional))]
+    pub required: Vec<StrokeRef>,
+    /// Strokes no glyph may contain.
+    #[cfg_attr(test, ts(as = "Option<Vec<StrokeRef>>", optional))]
+    pub forbidden: Vec<StrokeRef>,
+    /// Stroke sets no glyph may contain entirely.
+    #[cfg_attr(test, ts(as = "Option<Vec<Vec<StrokeRef>>>", optional))]
+    pub forbidden_sets: Vec<Vec<StrokeRef>>,
+    /// Stroke groups a glyph may use at most one stroke of.
+    #[cfg_attr(test, ts(as = "Option<Vec<Vec<StrokeRef>>>", optional))]
+    pub exclusive_groups: Vec<Vec<StrokeRef>>,
+    /// Contacts through which strokes connect, any contact when empty.
+    #[cfg_attr(test, ts(as = "Option<Vec<Contact>>", optional))]
+    pub linking_contacts: Vec<Contact>,
+    /// Contacts no two strokes of a glyph may make.
+    #[cfg_attr(test, ts(as = "Option<Vec<Contact>>", optional))]
+    pub forbidden_contacts: Vec<Contact>,
+}
+
+/// Constraints compiled to masks over glyph identifiers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ConstraintMasks {
+    pub required: u64,
+    pub forbidden: u64,
+    pub forbidden_sets: Vec<u64>,
+    pub exclusive_groups: Vec<u64>,
+    /// Strokes each stroke connects to, the intersection matrix deciding when empty.
+    pub links: Vec<u64>,
+}
+
+impl ConstraintMasks {
+    pub fn is_empty(&self) -> bool {
+        *self == ConstraintMasks::default()
+    }
+
+    /// Whether the glyph breaks none of the rules adding strokes cannot repair.
+    pub fn allows(&self, identifier: u64) -> bool {
+        identifier & self.forbidden == 0
+            && self
+                .forbidden_sets
+                .iter()
+                .all(|&set| identifier & set != set)
+            && self
+                .exclusive_groups
+                .iter()
+                .all(|&group| (identifier & group).count_ones() <= 1)
+    }
+
+    pub fn accepts(&self, identifier: u64) -> bool {
+        identifier & self.required == self.required && self.allows(identifier)
+    }
+
+    /// Whether the strokes of the glyph are connected through `links`, strokes without
+    /// links connecting to none.
+    pub fn connects(&self, identifier: u64) -> bool {
+        let mut reached = identifier & identifier.wrapping_neg();
+        loop {
+            let next = set_bits(reached).fold(reached, |acc, i| {
+                acc | (self.links.get(i).copied().unwrap_or(0) & identifier)
+            });
+            if next == reached {
+                return identifier != 0 && reached == identifier;
+            }
+            reached = next;
+        }
+    }
+}
+
+impl GlyphGenerator {
+    fn stroke_mask(&self, strokes: &[StrokeRef]) -> Result<u64, ResolveError> {
+        strokes.iter().try_fold(0, |acc, stroke| {
+            Ok(acc | self.from_stroke(&self.resolve(stroke)?).identifier)
+        })
+    }
+
+    pub fn constraint_masks(
+        &self,
+        constraints: &Constraints,
+    ) -> Result<ConstraintMasks, ResolveError> {
+        let mut masks = ConstraintMasks {
+            required: self.stroke_mask(&constraints.required)?,
+            forbidden: self.stroke_mask(&constraints.forbidden)?,
+            forbidden_sets: constraints
+                .forbidden_sets
+                .iter()
+                .map(|set| self.stroke_mask(set))
+                .collect::<Result<_, _>>()?,
+            exclusive_groups: constraints
+                .exclusive_groups
+                .iter()
+                .map(|group| self.stroke_mask(group))
+                .collect::<Result<_, _>>()?,
+            links: Vec::new(),
+        };
+
+        // Strokes past the identifier width belong to no glyph
+        let n = self.parameters.parent_strokes.len().min(MAX_PARENT_STROKES);
+        if !constraints.forbidden_contacts.is_empty() {
+            for i in 0..n {
+                for j in i + 1..n {
+                    if self
+                        .contacts(i, j)
+                        .intersects(&constraints.forbidden_contacts)
+                    {
+                        masks.forbidden_sets.push(1 << i | 1 << j);
+                    }
+                }
+            }
+        }
+        if !constraints.linking_contacts.is_empty() {
+            masks.links = (0..n)
+                .map(|i| {
+                    (0..n)
+                        .filter(|&j| {
+                            i == j
+                                || self.parameters.intersection_matrix[i][j] != 0
+                                    && self
+                                        .contacts(i, j)
+                                        .intersects(&constraints.linking_contacts)
+                        })
+                        .fold(0, |acc, j| acc | 1 << j)
+                })
+                .collect();
+        }
+        Ok(masks)
+    }
+
+    /// Whether the strokes of the glyph are connected, through the links of the masks if any.
+    pub fn is_linked(&self, glyph: &InternalGlyph, masks: &ConstraintMasks) -> bool {
+        if masks.links.is_empty() {
+            self.are_strokes_intersecting(glyph)
+        } else {
+            masks.connects(glyph.identifier)
+        }
+    }
+
+    /// The glyph itself if it meets the constraints, else its first image that does.
+    pub fn admissible(
+        &self,
+        glyph: &InternalGlyph,
+        masks: &ConstraintMasks,
+    ) -> Option<InternalGlyph> {
+        if masks.accepts(glyph.identifier) {
+            return Some(glyph.clone());
+        }
+        self.orbit_identifiers(glyph)
+            .into_iter()
+            .find(|&identifier| masks.accepts(identifier))
+            .map(InternalGlyph::from_identifier)
+    }
+
+    /// Same as `generate`, keeping the symmetry classes with a member meeting the
+    /// constraints and representing each by such a member.
+    pub fn generate_constrained(
+        &self,
+        strokes: &[Stroke],
+        seed: &Stroke,
+        constraints: &Constraints,
+    ) -> Result<Vec<Glyph>, ResolveError> {
+        let masks = self.constraint_masks(constraints)?;
+        Ok(self
+            .generate_levels(strokes, seed, &masks, |_, _, _, _| {})
+            .into_iter()
+            .flatten()
+            .filter_map(|g| self.admissible(&g, &masks))
+            .map(|g| self.to_glyph(&g))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::intersection_matrix;
+    use crate::parameters::Parameters;
+    use crate::schema::PARAMETERS_VERSION;
+    use crate::tests::setup;
+
+    #[test]
+    fn test_masks() {
+        let masks = ConstraintMasks {
+            required: 0b0001,
+            forbidden: 0b1000,
+            forbidden_sets: vec![0b0110],
+            exclusive_groups: vec![0b0101],
+            links: vec![0b0011, 0b0011, 0b0100, 0b1000],
+        };
+        assert!(masks.accepts(0b0011));
+        assert!(!masks.accepts(0b0010));
+        assert!(masks.allows(0b0010));
+        assert!(!masks.allows(0b1000));
+        assert!(!masks.allows(0b0110));
+        assert!(!masks.allows(0b0101));
+        assert!(ConstraintMasks::default().is_empty());
+        assert!(!masks.is_empty());
+        assert!(masks.connects(0b0011));
+        assert!(!masks.connects(0b0101));
+        assert!(!masks.connects(0));
+        assert!(masks.connects(1 << 63));
+        assert!(!masks.connects(1 << 63 | 0b0001));
+    }
+
+    #[test]
+    fn test_contact_masks_past_identifier_width() {
+        // Every chord of a 12-gon, 66 strokes
+        let points: Vec<(f64, f64)> = (0..12)
+            .map(|i| {
+                let angle = i as f64 * std::f64::consts::PI / 6.0;
+                (angle.cos(), angle.sin())
+            })
+            .collect();
+        let strokes: Vec<Stroke> = (0..12)
+            .flat_map(|i| (i + 1..12).map(move |j| (i, j)))
+            .map(|(i, j)| Stroke::line(points[i].0, points[i].1, points[j].0, points[j].1))
+            .collect();
+        assert!(strokes.len() > MAX_PARENT_STROKES);
+        let generator = GlyphGenerator::new(Parameters {
+            version: PARAMETERS_VERSION,
+            intersection_matrix: intersection_matrix(&strokes),
+            parent_strokes: strokes,
+            transformation_matrix: vec![],
+        });
+
+        let masks = generator
+            .constraint_masks(&Constraints {
+                linking_contacts: vec![Contact::Endpoint],
+                forbidden_contacts: vec![Contact::Crossing],
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(masks.links.len(), MAX_PARENT_STROKES);
+        assert!(masks
+            .forbidden_sets
+            .iter()
+            .all(|&set| set.count_ones() == 2));
+        // The chords from the first anchor meet there
+        assert!(masks.connects(0b111));
+    }
+
+    #[test]
+    fn test_generate_constrained() {
+        let context = setup();
+        let generator = context.basic;
+        let strokes = generator.parameters.parent_strokes.clone();
+        let refs: Vec<StrokeRef> = strokes.iter().map(|s| StrokeRef::Stroke(*s)).collect();
+
+        let unconstrained = generator.generate(&strokes, &strokes[0]);
+        assert_eq!(
+            generator.generate_constrained(&strokes, &strokes[0], &Constraints::default()),
+            Ok(unconstrained.clone())
+        );
+
+        // Bottom stroke required, never both diagonals, at most one of the verticals
+        let constraints = Constraints {
+            required: vec![refs[1]],
+            forbidden_sets: vec![vec![refs[2], refs[3]]],
+            exclusive_groups: vec![vec![refs[0], refs[5]]],
+            ..Constraints::default()
+        };
+        let constrained = generator
+            .generate_constrained(&strokes, &strokes[0], &constraints)
+            .unwrap();
+        assert!(!constrained.is_empty());
+        for glyph in &constrained {
+            assert!(glyph.strokes.contains(&strokes[1]));
+            assert!(!(glyph.strokes.contains(&strokes[2]) && glyph.strokes.contains(&strokes[3])));
+            assert!(!(glyph.strokes.contains(&strokes[0]) && glyph.strokes.contains(&strokes[5])));
+        }
+
+        // Pruning keeps every class with an admissible member
+        let masks = generator.constraint_masks(&constraints).unwrap();
+        let expected = unconstrained
+            .iter()
+            .filter(|g| {
+                generator
+                    .orbit_identifiers(&generator.from_glyph(g))
+                    .into_iter()
+                    .any(|identifier| masks.accepts(identifier))
+            })
+            .count();
+        assert_eq!(constrained.len(), expected);
+
+        let forbidden = Constraints {
+            forbidden: refs.clone(),
+            ..Constraints::default()
+        };
+        assert!(generator
+            .generate_constrained(&strokes, &strokes[0], &forbidden)
+            .unwrap()
+            .is_empty());
+
+        let unknown = Constraints {
+            required: vec![StrokeRef::Anchors([0, 0])],
+            ..Constraints::default()
+        };
+        assert_eq!(
+            generator.generate_constrained(&strokes, &strokes[0], &unknown),
+            Err(ResolveError::NoStrokeBetween(0, 0))
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use std::fmt;
+
+use crate::geometry::{end, start, stroke_intersections, strokes_overlap, Point, EPSILON};
+use crate::stroke::Stroke;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Way two strokes meet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum Contact {
+    /// An end of each stroke at the same point.
+    Endpoint,
+    /// An end of one stroke inside the other.
+    TJunction,
+    /// The strokes pass through each other.
+    Crossing,
+    /// The strokes share a piece of positive length.
+    Overlap,
+}
+
+impl Contact {
+    pub const ALL: [Contact; 4] = [
+        Contact::Endpoint,
+        Contact::TJunction,
+        Contact::Crossing,
+        Contact::Overlap,
+    ];
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+impl fmt::Display for Contact {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Contact::Endpoint => "endpoint join",
+            Contact::TJunction => "T-junction",
+            Contact::Crossing => "crossing",
+            Contact::Overlap => "overlap",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Set of contacts between two strokes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Contacts(u8);
+
+impl Contacts {
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, contact: Contact) -> bool {
+        self.0 & contact.bit() != 0
+    }
+
+    pub fn insert(&mut self, contact: Contact) {
+        self.0 |= contact.bit();
+    }
+
+    pub fn intersects(&self, contacts: &[Contact]) -> bool {
+        contacts.iter().any(|&contact| self.contains(contact))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Contact> + '_ {
+        Contact::ALL
+            .into_iter()
+            .filter(move |&contact| self.contains(contact))
+    }
+}
+
+impl FromIterator<Contact> for Contacts {
+    fn from_iter<I: IntoIterator<Item = Contact>>(iter: I) -> Self {
+        let mut contacts = Contacts::default();
+        iter.into_iter()
+            .for_each(|contact| contacts.insert(contact));
+        contacts
+    }
+}
+
+/// Contacts between two strokes. Overlapping strokes report the overlap, and an endpoint
+/// join if they also share an end, their other meeting points lying along the overlap;
+/// otherwise every shared point is classified by how many of the strokes end there.
+pub fn contacts(a: &Stroke, b: &Stroke) -> Contacts {
+    let is_end = |stroke: &Stroke, point: &Point| {
+        point.distance(&start(stroke)) < EPSILON || point.distance(&end(stroke)) < EPSILON
+    };
+    if strokes_overlap(a, b) {
+        let mut contacts = Contacts::from_iter([Contact::Overlap]);
+        if [start(a), end(a)].iter().any(|point| is_end(b, point)) {
+            contacts.insert(Contact::Endpoint);
+        }
+        return contacts;
+    }
+    stroke_intersections(a, b)
+        .iter()
+        .map(|point| match (is_end(a, point), is_end(b, point)) {
+            (true, true) => Contact::Endpoint,
+            (false, false) => Contact::Crossing,
+            _ => Contact::TJunction,
+        })
+        .collect()
+}
+
+/// Contacts of every pair of strokes, the diagonal included.
+pub fn contact_matrix(strokes: &[Stroke]) -> Vec<Vec<Contacts>> {
+    strokes
+        .iter()
+        .map(|a| strokes.iter().map(|b| contacts(a, b)).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::Constraints;
+    use crate::tests::setup;
+
+    #[test]
+    fn test_contacts() {
+        let diagonal = Stroke::line(-1.0, -1.0, 1.0, 1.0);
+        let kinds = |stroke: Stroke| contacts(&diagonal, &stroke).iter().collect::<Vec<_>>();
+        assert_eq!(
+            kinds(Stroke::line(1.0, 1.0, 1.0, -1.0)),
+            vec![Contact::Endpoint]
+        );
+        assert_eq!(
+            kinds(Stroke::line(0.0, 0.0, 1.0, -1.0)),
+            vec![Contact::TJunction]
+        );
+        assert_eq!(
+            kinds(Stroke::line(-1.0, 1.0, 1.0, -1.0)),
+            vec![Contact::Crossing]
+        );
+        assert_eq!(
+            kinds(Stroke::line(0.0, 0.0, 2.0, 2.0)),
+            vec![Contact::Overlap]
+        );
+        assert_eq!(
+            kinds(Stroke::line(0.0, 0.0, -1.0, -1.0)),
+            vec![Contact::Endpoint, Contact::Overlap]
+        );
+        assert_eq!(kinds(diagonal), vec![Contact::Endpoint, Contact::Overlap]);
+        assert!(contacts(&diagonal, &Stroke::line(1.0, -1.0, 2.0, 0.0)).is_empty());
+
+        let arc = Stroke::arc(-1.0, -1.0, 1.0, 1.0, 1.0);
+        let radius = contacts(&arc, &Stroke::line(0.0, 0.0, 2.0, 0.0));
+        assert_eq!(radius.iter().collect::<Vec<_>>(), vec![Contact::Crossing]);
+        assert!(contacts(&arc, &diagonal).contains(Contact::Endpoint));
+    }
+
+    #[test]
+    fn test_generate_contact_rules() {
+        let context = setup();
+        let generator = context.basic;
+        let strokes = generator.parameters.parent_strokes.clone();
+        assert!(generator.contacts(0, 1).contains(Contact::Endpoint));
+        assert!(generator.contacts(2, 3).contains(Contact::Crossing));
+        let contacts: Vec<Contacts> = (0..36)
+            .flat_map(|i| (0..36).map(move |j| (i, j)))
+            .map(|(i, j)| context.advanced.contacts(i, j))
+            .collect();
+        for contact in Contact::ALL {
+            assert!(contacts.iter().any(|c| c.contains(contact)));
+        }
+
+        let unconstrained = generator.generate(&strokes, &strokes[0]);
+        let no_crossings = Constraints {
+            forbidden_contacts: vec![Contact::Crossing],
+            ..Constraints::default()
+        };
+        let glyphs = generator
+            .generate_constrained(&strokes, &strokes[0], &no_crossings)
+            .unwrap();
+        assert!(glyphs.len() < unconstrained.len());
+        for glyph in &glyphs {
+            assert!(!(glyph.strokes.contains(&strokes[2]) && glyph.strokes.contains(&strokes[3])));
+        }
+
+        // The diagonals only cross each other
+        let diagonals = [strokes[2], strokes[3]];
+        assert_eq!(generator.generate(&diagonals, &diagonals[0]).len(), 2);
+        let endpoints_only = Constraints {
+            linking_contacts: vec![Contact::Endpoint],
+            ..Constraints::default()
+        };
+        assert_eq!(
+            generator
+                .generate_constrained(&diagonals, &diagonals[0], &endpoints_only)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            generator
+                .generate_constrained(&strokes, &strokes[0], &endpoints_only)
+                .unwrap()
+                .len(),
+            unconstrained.len()
+        );
+    }
+}
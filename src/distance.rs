@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+
+use crate::generate::GlyphGenerator;
+use crate::geometry::{point_at, point_stroke_distance, PlanarGraph};
+use crate::glyph::Glyph;
+use crate::stroke::Stroke;
+use serde::{Deserialize, Serialize};
+
+const HAUSDORFF_SAMPLES: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    /// Number of strokes present in exactly one of the glyphs.
+    StrokeDifference,
+    /// Stroke difference minimized over the symmetry orbit of the second glyph.
+    SymmetricStrokeDifference,
+    /// Hausdorff distance between the drawn strokes.
+    Hausdorff,
+    /// Vertex and edge insertions/deletions between the planar graphs, vertices matched by position.
+    GraphEdit,
+}
+
+pub fn stroke_difference(a: &Glyph, b: &Glyph) -> f64 {
+    let a: HashSet<&Stroke> = a.strokes.iter().collect();
+    let b: HashSet<&Stroke> = b.strokes.iter().collect();
+    a.symmetric_difference(&b).count() as f64
+}
+
+fn directed_hausdorff(a: &Glyph, b: &Glyph) -> f64 {
+    a.strokes
+        .iter()
+        .flat_map(|stroke| {
+            (0..=HAUSDORFF_SAMPLES)
+                .map(move |i| point_at(stroke, i as f64 / HAUSDORFF_SAMPLES as f64))
+        })
+        .map(|point| {
+            b.strokes
+                .iter()
+                .map(|stroke| point_stroke_distance(&point, stroke))
+                .fold(f64::INFINITY, f64::min)
+        })
+        .fold(0.0, f64::max)
+}
+
+/// Hausdorff distance between the drawings, approximated by sampling each stroke.
+pub fn hausdorff(a: &Glyph, b: &Glyph) -> f64 {
+    match (a.strokes.is_empty(), b.strokes.is_empty()) {
+        (true, true) => 0.0,
+        (true, false) | (false, true) => f64::INFINITY,
+        (false, false) => directed_hausdorff(a, b).max(directed_hausdorff(b, a)),
+    }
+}
+
+pub fn graph_edit(a: &Glyph, b: &Glyph) -> f64 {
+    let (a, b) = (
+        PlanarGraph::from_strokes(&a.strokes),
+        PlanarGraph::from_strokes(&b.strokes),
+    );
+    let vertices = |graph: &PlanarGraph| -> HashSet<(i64, i64)> {
+        graph.vertices.iter().map(|p| p.key()).collect()
+    };
+    let edges = |graph: &PlanarGraph| -> HashSet<((i64, i64), (i64, i64))> {
+        graph
+            .edges
+            .iter()
+            .map(|&(i, j)| (graph.vertices[i].key(), graph.vertices[j].key()))
+            .map(|(p, q)| (p.min(q), p.max(q)))
+            .collect()
+    };
+    let vertex_edits = vertices(&a).symmetric_difference(&vertices(&b)).count();
+    let edge_edits = edges(&a).symmetric_difference(&edges(&b)).count();
+    (vertex_edits + edge_edits) as f64
+}
+
+impl GlyphGenerator {
+    /// The glyph followed by its images under every transformation of the parameters.
+    pub fn orbit(&self, glyph: &Glyph) -> Vec<Glyph> {
+        let internal = self.from_glyph(glyph);
+        std::iter::once(glyph.clone())
+            .chain(self.transform(&internal).iter().map(|g| self.to_glyph(g)))
+            .collect()
+    }
+
+    pub fn symmetric_stroke_difference(&self, a: &Glyph, b: &Glyph) -> f64 {
+        self.orbit(b)
+            .iter()
+            .map(|g| stroke_difference(a, g))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    pub fn distance(&self, metric: Metric, a: &Glyph, b: &Glyph) -> f64 {
+        match metric {
+            Metric::StrokeDifference => stroke_difference(a, b),
+            Metric::SymmetricStrokeDifference => self.symmetric_stroke_difference(a, b),
+            Metric::Hausdorff => hausdorff(a, b),
+            Metric::GraphEdit => graph_edit(a, b),
+        }
+    }
+
+    /// Indices and distances of the `k` glyphs closest to `glyph`, closest first.
+    pub fn nearest(
+        &self,
+        metric: Metric,
+        glyph: &Glyph,
+        glyphs: &[Glyph],
+        k: usize,
+    ) -> Vec<(usize, f64)> {
+        let mut distances: Vec<(usize, f64)> = glyphs
+            .iter()
+            .enumerate()
+            .map(|(i, other)| (i, self.distance(metric, glyph, other)))
+            .collect();
+        distances.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        distances.truncate(k);
+        distances
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::setup;
+
+    fn glyph(strokes: &[(f64, f64, f64, f64)]) -> Glyph {
+        Glyph {
+            strokes: strokes
+                .iter()
+                .map(|&(x0, y0, x1, y1)| Stroke::line(x0, y0, x1, y1))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_stroke_difference() {
+        let a = glyph(&[(-1.0, -1.0, -1.0, 1.0), (-1.0, 1.0, 1.0, 1.0)]);
+        let b = glyph(&[(1.0, 1.0, -1.0, 1.0), (1.0, -1.0, 1.0, 1.0)]);
+        assert_eq!(stroke_difference(&a, &a), 0.0);
+        assert_eq!(stroke_difference(&a, &b), 2.0);
+    }
+
+    #[test]
+    fn test_hausdorff() {
+        let a = glyph(&[(-1.0, -1.0, -1.0, 1.0)]);
+        let b = glyph(&[(1.0, -1.0, 1.0, 1.0)]);
+        let c = glyph(&[(-1.0, -1.0, -1.0, 0.0)]);
+        assert_eq!(hausdorff(&a, &a), 0.0);
+        assert!((hausdorff(&a, &b) - 2.0).abs() < 1e-9);
+        assert!((hausdorff(&a, &c) - 1.0).abs() < 1e-9);
+        assert_eq!(hausdorff(&a, &c), hausdorff(&c, &a));
+    }
+
+    #[test]
+    fn test_graph_edit() {
+        let full = glyph(&[(-1.0, -1.0, 1.0, 1.0)]);
+        let halves = glyph(&[(-1.0, -1.0, 0.0, 0.0), (0.0, 0.0, 1.0, 1.0)]);
+        assert_eq!(graph_edit(&full, &full), 0.0);
+        // One extra vertex at the centre, two edges instead of one
+        assert_eq!(graph_edit(&full, &halves), 4.0);
+    }
+
+    #[test]
+    fn test_symmetric_stroke_difference() {
+        let context = setup();
+        let generator = context.basic;
+        let strokes = &generator.parameters.parent_strokes;
+        let (th, lv, rv, bh) = (strokes[0], strokes[1], strokes[4], strokes[5]);
+
+        let left = Glyph {
+            strokes: vec![th, lv],
+        };
+        let right = Glyph {
+            strokes: vec![rv, bh],
+        };
+        assert_eq!(
+            generator.distance(Metric::StrokeDifference, &left, &right),
+            4.0
+        );
+        assert_eq!(
+            generator.distance(Metric::SymmetricStrokeDifference, &left, &right),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_nearest() {
+        let context = setup();
+        let generator = context.basic;
+        let strokes = generator.parameters.parent_strokes.clone();
+        let glyphs = generator.generate(&strokes, &strokes[0]);
+        let target = glyphs[3].clone();
+
+        for metric in [
+            Metric::StrokeDifference,
+            Metric::SymmetricStrokeDifference,
+            Metric::Hausdorff,
+            Metric::GraphEdit,
+        ] {
+            let nearest = generator.nearest(metric, &target, &glyphs, 3);
+            assert_eq!(nearest.len(), 3);
+            assert_eq!(nearest[0], (3, 0.0));
+            assert!(nearest.windows(2).all(|w| w[0].1 <= w[1].1));
+        }
+    }
+}
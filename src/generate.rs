@@ -1,23 +1,143 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::ControlFlow;
+
+use crate::constraints::ConstraintMasks;
+use crate::contact::{contact_matrix, Contacts};
+use crate::geometry::{end, start, Point};
 use crate::glyph::Glyph;
 use crate::glyph::InternalGlyph;
 use crate::intersect::are_strokes_linked;
 use crate::parameters::Parameters;
 use crate::stroke::InternalStroke;
 use crate::stroke::Stroke;
+use crate::stroke::StrokeKey;
+use crate::stroke::StrokeRef;
+use crate::stroke::DEFAULT_QUANTUM;
+use once_cell::sync::OnceCell;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolveError {
+    /// No parent stroke joins the two anchors.
+    NoStrokeBetween(usize, usize),
+    /// The stroke is not among the parent strokes.
+    UnknownStroke(Stroke),
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResolveError::NoStrokeBetween(a, b) => {
+                write!(f, "Could not find stroke between anchors {a} and {b}")
+            }
+            ResolveError::UnknownStroke(stroke) => {
+                write!(f, "Could not find stroke {stroke:?} in parent strokes")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {}
 
 #[derive(Debug, Clone)]
 pub struct GlyphGenerator {
     pub parameters: Parameters,
+    /// Grid step coordinates are snapped to when matching strokes against parent strokes.
+    pub quantum: f64,
+    anchors: Vec<Point>,
+    anchor_index: HashMap<(i64, i64), usize>,
+    stroke_index: HashMap<StrokeKey, usize>,
+    anchor_pair_index: HashMap<(usize, usize), usize>,
+    /// Built on first use, as only contact constraints need it.
+    contacts: OnceCell<Vec<Vec<Contacts>>>,
 }
 
 impl GlyphGenerator {
     pub fn new(parameters: Parameters) -> Self {
-        Self { parameters }
+        Self::with_quantum(parameters, DEFAULT_QUANTUM)
+    }
+
+    pub fn with_quantum(parameters: Parameters, quantum: f64) -> Self {
+        let snap = |p: &Point| {
+            (
+                (p.x / quantum).round() as i64,
+                (p.y / quantum).round() as i64,
+            )
+        };
+        let anchors = parameters.anchors();
+        let anchor_index: HashMap<(i64, i64), usize> = anchors
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (snap(p), i))
+            .collect();
+
+        let mut stroke_index: HashMap<StrokeKey, usize> = HashMap::new();
+        let mut anchor_pair_index: HashMap<(usize, usize), usize> = HashMap::new();
+        for (i, stroke) in parameters.parent_strokes.iter().enumerate() {
+            stroke_index.entry(stroke.key(quantum)).or_insert(i);
+            let a = anchor_index.get(&snap(&start(stroke)));
+            let b = anchor_index.get(&snap(&end(stroke)));
+            if let (Some(&a), Some(&b)) = (a, b) {
+                anchor_pair_index.entry((a.min(b), a.max(b))).or_insert(i);
+            }
+        }
+
+        Self {
+            parameters,
+            quantum,
+            anchors,
+            anchor_index,
+            stroke_index,
+            anchor_pair_index,
+            contacts: OnceCell::new(),
+        }
+    }
+
+    /// Stroke endpoints, identified by their position in this list.
+    pub fn anchors(&self) -> &[Point] {
+        &self.anchors
+    }
+
+    /// Contacts between two parent strokes, from their geometry.
+    pub fn contacts(&self, a: usize, b: usize) -> Contacts {
+        self.contacts
+            .get_or_init(|| contact_matrix(&self.parameters.parent_strokes))[a][b]
+    }
+
+    pub fn anchor_id(&self, point: &Point) -> Option<usize> {
+        let key = (
+            (point.x / self.quantum).round() as i64,
+            (point.y / self.quantum).round() as i64,
+        );
+        self.anchor_index.get(&key).copied()
+    }
+
+    /// Index of the parent stroke joining two anchors, the first one listed if several do.
+    pub fn stroke_between(&self, a: usize, b: usize) -> Option<usize> {
+        self.anchor_pair_index.get(&(a.min(b), a.max(b))).copied()
+    }
+
+    /// The parent stroke a reference designates.
+    pub fn resolve(&self, stroke: &StrokeRef) -> Result<Stroke, ResolveError> {
+        match stroke {
+            StrokeRef::Anchors([a, b]) => {
+                let index = self
+                    .stroke_between(*a, *b)
+                    .ok_or(ResolveError::NoStrokeBetween(*a, *b))?;
+                Ok(self.parameters.parent_strokes[index])
+            }
+            StrokeRef::Stroke(stroke) => {
+                if !self.stroke_index.contains_key(&stroke.key(self.quantum)) {
+                    return Err(ResolveError::UnknownStroke(*stroke));
+                }
+                Ok(*stroke)
+            }
+        }
     }
 
     pub fn are_strokes_intersecting(&self, glyph: &InternalGlyph) -> bool {
         let indices: Vec<usize> = glyph.strokes.iter().map(|stroke| stroke.index).collect();
-        return are_strokes_linked(&indices, &self.parameters.intersection_matrix);
+        are_strokes_linked(&indices, &self.parameters.intersection_matrix)
     }
 
     pub fn transform(&self, glyph: &InternalGlyph) -> Vec<InternalGlyph> {
@@ -37,71 +157,476 @@ impl GlyphGenerator {
             .collect()
     }
 
+    /// Identifiers of the glyph and of its images, identity first.
+    pub fn orbit_identifiers(&self, glyph: &InternalGlyph) -> Vec<u64> {
+        std::iter::once(glyph.identifier)
+            .chain(self.transform(glyph).iter().map(|g| g.identifier))
+            .collect()
+    }
+
+    /// Smallest identifier over the symmetry orbit, shared by all images of a glyph.
+    pub fn canonical_identifier(&self, glyph: &InternalGlyph) -> u64 {
+        self.orbit_identifiers(glyph).into_iter().min().unwrap()
+    }
+
     pub fn to_glyph(&self, glyph: &InternalGlyph) -> Glyph {
         Glyph {
             strokes: glyph
                 .strokes
                 .iter()
-                .map(|s| self.parameters.parent_strokes[s.index].clone())
+                .map(|s| self.parameters.parent_strokes[s.index])
                 .collect(),
         }
     }
 
+    #[allow(clippy::wrong_self_convention)]
     pub fn from_stroke(&self, stroke: &Stroke) -> InternalGlyph {
-        self.parameters
-            .parent_strokes
-            .iter()
-            .enumerate()
-            .find_map(|(i, parent_stroke)| {
-                if stroke == parent_stroke || stroke.is_inverse(parent_stroke) {
-                    Some(InternalGlyph::from_stroke(InternalStroke { index: i }))
-                } else {
-                    None
-                }
-            })
-            .expect("Could not find stroke in parent strokes")
+        let index = self
+            .stroke_index
+            .get(&stroke.key(self.quantum))
+            .expect("Could not find stroke in parent strokes");
+        InternalGlyph::from_stroke(InternalStroke { index: *index })
     }
 
-    pub fn generate(&self, strokes: &[Stroke], seed: &Stroke) -> Vec<Glyph> {
-        let n = strokes.len();
-        let seed_internal = self.from_stroke(seed);
-        let strokes_internal: Vec<InternalGlyph> = strokes
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_glyph(&self, glyph: &Glyph) -> InternalGlyph {
+        glyph
+            .strokes
             .iter()
             .map(|stroke| self.from_stroke(stroke))
-            .collect();
+            .fold(InternalGlyph::empty(), |acc, stroke| acc.union(&stroke))
+    }
 
-        let mut glyphs: Vec<Vec<InternalGlyph>> = vec![Vec::new(); n];
-        glyphs[0].push(seed_internal);
+    /// Connected glyphs made of a glyph of `level` plus one of `strokes`, one per symmetry
+    /// class, ordered by canonical identifier. `on_edge` receives, for every such extension,
+    /// the index of the extended glyph, the index of the added stroke and the index of the
+    /// resulting class in the next level. Classes without a member allowed by `masks` are pruned,
+    /// and strokes connect through the links of `masks` if it has some.
+    pub fn expand(
+        &self,
+        level: &[InternalGlyph],
+        strokes: &[InternalGlyph],
+        masks: &ConstraintMasks,
+        on_edge: impl FnMut(usize, usize, usize),
+    ) -> Vec<InternalGlyph> {
+        let mut expansion = Expansion::default();
+        while !expansion.is_done(level) {
+            expansion.advance(self, level, strokes, masks);
+        }
+        expansion.finish(self, on_edge)
+    }
 
-        for i in 0..n - 1 {
-            let mut next_glyphs = Vec::new();
+    /// Generation levels, level i holding the glyphs made of the seed and i other strokes,
+    /// each level ordered by canonical identifier.
+    pub fn generate_levels(
+        &self,
+        strokes: &[Stroke],
+        seed: &Stroke,
+        masks: &ConstraintMasks,
+        mut on_edge: impl FnMut(usize, usize, usize, usize),
+    ) -> Vec<Vec<InternalGlyph>> {
+        let mut generation = Generation::new(self, strokes, seed, masks.clone());
+        let mut levels = vec![generation.level().to_vec()];
+        while !generation.is_done() {
+            if generation.step(self, &mut on_edge) {
+                levels.push(generation.level().to_vec());
+            }
+        }
+        levels
+    }
 
-            for glyph in &glyphs[i] {
-                for stroke in &strokes_internal {
-                    let next_glyph = glyph.union(stroke);
-                    if next_glyph.strokes.len() < i + 2 {
-                        continue;
-                    }
-                    if next_glyphs.contains(&next_glyph) {
-                        continue;
-                    }
-                    if self.are_strokes_intersecting(&next_glyph) {
-                        let transformed_glyphs = self.transform(&next_glyph);
-                        if transformed_glyphs.iter().all(|g| !next_glyphs.contains(&g)) {
-                            next_glyphs.push(next_glyph);
-                        }
-                    }
-                }
+    /// Connected extensions of the starting glyphs by 1 to `extra_strokes` strokes of the
+    /// palette, one per symmetry class, by increasing number of added strokes. An empty
+    /// palette stands for all parent strokes.
+    pub fn extend(&self, starts: &[Glyph], palette: &[Stroke], extra_strokes: usize) -> Vec<Glyph> {
+        let palette_internal: Vec<InternalGlyph> = if palette.is_empty() {
+            (0..self.parameters.parent_strokes.len())
+                .map(|index| InternalGlyph::from_stroke(InternalStroke { index }))
+                .collect()
+        } else {
+            palette
+                .iter()
+                .map(|stroke| self.from_stroke(stroke))
+                .collect()
+        };
+
+        let mut seen: HashSet<u64> = HashSet::new();
+        let mut level: Vec<InternalGlyph> = Vec::new();
+        for start in starts {
+            let glyph = self.from_glyph(start);
+            if seen.insert(self.canonical_identifier(&glyph)) {
+                level.push(glyph);
             }
+        }
 
-            glyphs[i + 1].extend(next_glyphs);
+        let mut extensions: Vec<InternalGlyph> = Vec::new();
+        for _ in 0..extra_strokes {
+            level = self.expand(
+                &level,
+                &palette_internal,
+                &ConstraintMasks::default(),
+                |_, _, _| {},
+            );
+            level.retain(|glyph| seen.insert(self.canonical_identifier(glyph)));
+            if level.is_empty() {
+                break;
+            }
+            extensions.extend(level.iter().cloned());
         }
 
+        extensions.iter().map(|g| self.to_glyph(g)).collect()
+    }
+
+    /// Glyphs by increasing number of strokes, then by canonical identifier, the strokes of
+    /// each glyph in parent stroke order.
+    pub fn generate(&self, strokes: &[Stroke], seed: &Stroke) -> Vec<Glyph> {
         // Convert and flatten the glyphs to the desired type
-        glyphs
+        self.generate_levels(strokes, seed, &ConstraintMasks::default(), |_, _, _, _| {})
             .into_iter()
             .flatten()
             .map(|g| self.to_glyph(&g))
             .collect()
     }
 }
+
+/// Expansion of a level in progress, one glyph of the level at a time, as done by
+/// `GlyphGenerator::expand`.
+#[derive(Debug, Clone, Default)]
+pub struct Expansion {
+    from: usize,
+    next_glyphs: Vec<InternalGlyph>,
+    edges: Vec<(usize, usize, usize)>,
+}
+
+impl Expansion {
+    /// Expansion resumed after `expanded` glyphs of the level, with the classes found by
+    /// then. The edges found by then are lost.
+    pub fn resume(expanded: usize, found: Vec<InternalGlyph>) -> Self {
+        Expansion {
+            from: expanded,
+            next_glyphs: found,
+            edges: Vec::new(),
+        }
+    }
+
+    pub fn is_done(&self, level: &[InternalGlyph]) -> bool {
+        self.from >= level.len()
+    }
+
+    /// Number of glyphs of the level expanded so far.
+    pub fn expanded(&self) -> usize {
+        self.from
+    }
+
+    /// Number of classes of the next level found so far.
+    pub fn found(&self) -> usize {
+        self.next_glyphs.len()
+    }
+
+    /// Identifiers of the classes of the next level found so far, in the order found.
+    pub fn found_identifiers(&self) -> Vec<u64> {
+        self.next_glyphs.iter().map(|g| g.identifier).collect()
+    }
+
+    /// Adds the extensions of the next glyph of the level.
+    pub fn advance(
+        &mut self,
+        generator: &GlyphGenerator,
+        level: &[InternalGlyph],
+        strokes: &[InternalGlyph],
+        masks: &ConstraintMasks,
+    ) {
+        let from = self.from;
+        let glyph = &level[from];
+        self.from += 1;
+
+        for (stroke_index, stroke) in strokes.iter().enumerate() {
+            let next_glyph = glyph.union(stroke);
+            if next_glyph.identifier == glyph.identifier {
+                continue;
+            }
+            if let Some(to) = self.next_glyphs.iter().position(|g| *g == next_glyph) {
+                self.edges.push((from, stroke_index, to));
+                continue;
+            }
+            if generator.is_linked(&next_glyph, masks) {
+                let transformed_glyphs = generator.transform(&next_glyph);
+                if !masks.is_empty()
+                    && !std::iter::once(&next_glyph)
+                        .chain(&transformed_glyphs)
+                        .any(|g| masks.allows(g.identifier))
+                {
+                    continue;
+                }
+                match self
+                    .next_glyphs
+                    .iter()
+                    .position(|g| transformed_glyphs.contains(g))
+                {
+                    Some(to) => self.edges.push((from, stroke_index, to)),
+                    None => {
+                        self.next_glyphs.push(next_glyph);
+                        self.edges
+                            .push((from, stroke_index, self.next_glyphs.len() - 1));
+                    }
+                }
+            }
+        }
+    }
+
+    /// The next level, ordered by canonical identifier, reporting the edges as `expand` does.
+    pub fn finish(
+        self,
+        generator: &GlyphGenerator,
+        mut on_edge: impl FnMut(usize, usize, usize),
+    ) -> Vec<InternalGlyph> {
+        let mut order: Vec<usize> = (0..self.next_glyphs.len()).collect();
+        order.sort_by_cached_key(|&i| generator.canonical_identifier(&self.next_glyphs[i]));
+        let mut position = vec![0; order.len()];
+        for (new, &old) in order.iter().enumerate() {
+            position[old] = new;
+        }
+        for (from, stroke_index, to) in self.edges {
+            on_edge(from, stroke_index, position[to]);
+        }
+        order
+            .into_iter()
+            .map(|i| self.next_glyphs[i].clone())
+            .collect()
+    }
+}
+
+/// Generation from a seed one level at a time, and each level one glyph at a time, as done
+/// by `generate_levels`. Observed, stepped and resumable generations drive it with `run`.
+#[derive(Debug, Clone)]
+pub struct Generation {
+    strokes: Vec<InternalGlyph>,
+    masks: ConstraintMasks,
+    levels: usize,
+    level_index: usize,
+    level: Vec<InternalGlyph>,
+    expansion: Expansion,
+}
+
+impl Generation {
+    /// Generation from the seed, which is dropped when `masks` allows none of its images.
+    pub fn new(
+        generator: &GlyphGenerator,
+        strokes: &[Stroke],
+        seed: &Stroke,
+        masks: ConstraintMasks,
+    ) -> Self {
+        let seed = generator.from_stroke(seed);
+        let level = if generator
+            .orbit_identifiers(&seed)
+            .into_iter()
+            .any(|identifier| masks.allows(identifier))
+        {
+            vec![seed]
+        } else {
+            vec![]
+        };
+        Self::resume(generator, strokes, masks, 0, level, Expansion::default())
+    }
+
+    /// Generation resumed while expanding `level`, the level `level_index`.
+    pub fn resume(
+        generator: &GlyphGenerator,
+        strokes: &[Stroke],
+        masks: ConstraintMasks,
+        level_index: usize,
+        level: Vec<InternalGlyph>,
+        expansion: Expansion,
+    ) -> Self {
+        Generation {
+            strokes: strokes.iter().map(|s| generator.from_stroke(s)).collect(),
+            masks,
+            levels: strokes.len(),
+            level_index,
+            level,
+            expansion,
+        }
+    }
+
+    /// Number of levels, one per stroke.
+    pub fn levels(&self) -> usize {
+        self.levels
+    }
+
+    /// Level being expanded, the seed being level 0.
+    pub fn level_index(&self) -> usize {
+        self.level_index
+    }
+
+    /// Glyphs of the level being expanded, the last one completed.
+    pub fn level(&self) -> &[InternalGlyph] {
+        &self.level
+    }
+
+    pub fn expansion(&self) -> &Expansion {
+        &self.expansion
+    }
+
+    pub fn masks(&self) -> &ConstraintMasks {
+        &self.masks
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.level_index + 1 >= self.levels
+    }
+
+    /// Expands the next glyph of the level or, once all are, completes the next level,
+    /// returning whether it did. `on_edge` receives the edges of the completed level as in
+    /// `generate_levels`.
+    pub fn step(
+        &mut self,
+        generator: &GlyphGenerator,
+        mut on_edge: impl FnMut(usize, usize, usize, usize),
+    ) -> bool {
+        if !self.expansion.is_done(&self.level) {
+            self.expansion
+                .advance(generator, &self.level, &self.strokes, &self.masks);
+            return false;
+        }
+        let index = self.level_index;
+        self.level = std::mem::take(&mut self.expansion).finish(generator, |from, stroke, to| {
+            on_edge(index, from, stroke, to)
+        });
+        self.level_index += 1;
+        true
+    }
+
+    /// Steps until the generation is done or `control` breaks, calling it after every step
+    /// with whether the step completed a level.
+    pub fn run<B>(
+        &mut self,
+        generator: &GlyphGenerator,
+        mut on_edge: impl FnMut(usize, usize, usize, usize),
+        mut control: impl FnMut(&Generation, bool) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        while !self.is_done() {
+            let completed = self.step(generator, &mut on_edge);
+            control(self, completed)?;
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{load_parameters, setup};
+
+    #[test]
+    fn test_from_stroke_after_arithmetic() {
+        let context = setup();
+        let generator = context.advanced;
+
+        // Centre anchor carrying floating point noise, as computed on the JavaScript side
+        let stroke = Stroke::line(1.0, 1.0, 0.1 + 0.2 - 0.3, 1e-15);
+        let glyph = generator.to_glyph(&generator.from_stroke(&stroke));
+        assert_eq!(glyph.strokes[0], Stroke::line(1.0, 1.0, 0.0, 0.0));
+
+        let coarse = GlyphGenerator::with_quantum(load_parameters("parameters_9ap.json"), 1e-3);
+        let stroke = Stroke::line(0.0004, -0.0002, 1.0, 0.9999);
+        let glyph = coarse.to_glyph(&coarse.from_stroke(&stroke));
+        assert_eq!(glyph.strokes[0], Stroke::line(1.0, 1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_anchor_index() {
+        let context = setup();
+        let generator = context.advanced;
+
+        assert_eq!(generator.anchors().len(), 9);
+        let centre = generator.anchor_id(&Point::new(0.0, 0.0)).unwrap();
+        let corner = generator.anchor_id(&Point::new(1.0, 1.0)).unwrap();
+        assert_eq!((centre, corner), (4, 3));
+
+        let index = generator.stroke_between(corner, centre).unwrap();
+        assert_eq!(index, generator.stroke_between(centre, corner).unwrap());
+        assert_eq!(
+            generator.parameters.parent_strokes[index],
+            Stroke::line(1.0, 1.0, 0.0, 0.0)
+        );
+        assert_eq!(generator.stroke_between(centre, centre), None);
+
+        let stroke: StrokeRef = serde_json::from_str("[3, 4]").unwrap();
+        assert_eq!(
+            generator.resolve(&stroke),
+            Ok(Stroke::line(0.0, 0.0, 1.0, 1.0))
+        );
+        let stroke: StrokeRef =
+            serde_json::from_str("{\"x0\":0,\"y0\":0,\"x1\":1,\"y1\":1}").unwrap();
+        assert_eq!(
+            generator.resolve(&stroke),
+            Ok(Stroke::line(0.0, 0.0, 1.0, 1.0))
+        );
+        assert_eq!(
+            generator.resolve(&StrokeRef::Anchors([centre, centre])),
+            Err(ResolveError::NoStrokeBetween(centre, centre))
+        );
+        let outside = Stroke::line(5.0, 5.0, 6.0, 6.0);
+        assert_eq!(
+            generator.resolve(&StrokeRef::Stroke(outside)),
+            Err(ResolveError::UnknownStroke(outside))
+        );
+    }
+
+    #[test]
+    fn test_extend() {
+        let context = setup();
+        let generator = context.basic;
+        let strokes = generator.parameters.parent_strokes.clone();
+
+        // Extending the seed by every stroke enumerates the same glyphs as generate
+        let seed = Glyph {
+            strokes: vec![strokes[0]],
+        };
+        let generated = generator.generate(&strokes, &strokes[0]);
+        let extensions = generator.extend(std::slice::from_ref(&seed), &strokes, strokes.len());
+        assert_eq!(extensions, generated[1..].to_vec());
+        assert_eq!(
+            generator.extend(std::slice::from_ref(&seed), &[], strokes.len()),
+            extensions
+        );
+
+        let one = generator.extend(std::slice::from_ref(&seed), &strokes, 1);
+        assert!(one.iter().all(|g| g.strokes.len() == 2));
+        let two = generator.extend(std::slice::from_ref(&seed), &strokes, 2);
+        assert_eq!(two[..one.len()], one[..]);
+        assert!(two[one.len()..].iter().all(|g| g.strokes.len() == 3));
+
+        // Symmetric starting glyphs are only extended once
+        let mirrored = Glyph {
+            strokes: vec![strokes[5]],
+        };
+        assert_eq!(generator.extend(&[seed, mirrored], &strokes, 2), two);
+    }
+
+    #[test]
+    fn test_deterministic_order() {
+        let context = setup();
+        let generator = context.basic;
+        let strokes = generator.parameters.parent_strokes.clone();
+        let glyphs = generator.generate(&strokes, &strokes[0]);
+
+        let rebuilt = GlyphGenerator::new(generator.parameters.clone());
+        assert_eq!(
+            serde_json::to_string(&rebuilt.generate(&strokes, &strokes[0])).unwrap(),
+            serde_json::to_string(&glyphs).unwrap()
+        );
+
+        let keys: Vec<(usize, u64)> = glyphs
+            .iter()
+            .map(|g| {
+                let internal = generator.from_glyph(g);
+                let indices: Vec<usize> = internal.strokes.iter().map(|s| s.index).collect();
+                assert!(indices.windows(2).all(|w| w[0] < w[1]));
+                assert_eq!(generator.to_glyph(&internal).strokes, g.strokes);
+                (g.strokes.len(), generator.canonical_identifier(&internal))
+            })
+            .collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+    }
+}
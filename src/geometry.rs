@@ -0,0 +1,435 @@
+use std::collections::HashMap;
+
+use crate::stroke::{Stroke, StrokeKind, DEFAULT_QUANTUM};
+
+pub const EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Self {
+        Point { x, y }
+    }
+
+    pub fn distance(&self, other: &Point) -> f64 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
+
+    /// Quantized coordinates, used to hash and compare points built by arithmetic.
+    pub fn key(&self) -> (i64, i64) {
+        (
+            (self.x / DEFAULT_QUANTUM).round() as i64,
+            (self.y / DEFAULT_QUANTUM).round() as i64,
+        )
+    }
+}
+
+pub fn start(stroke: &Stroke) -> Point {
+    Point::new(stroke.x0, stroke.y0)
+}
+
+pub fn end(stroke: &Stroke) -> Point {
+    Point::new(stroke.x1, stroke.y1)
+}
+
+/// Number of straight pieces used to approximate a curved stroke.
+pub const CURVE_SEGMENTS: usize = 16;
+
+/// Centre and signed swept angle of an arc stroke with non-zero bulge.
+fn arc_geometry(stroke: &Stroke, bulge: f64) -> (Point, f64) {
+    let (dx, dy) = (stroke.x1 - stroke.x0, stroke.y1 - stroke.y0);
+    // Offset of the centre from the chord midpoint, along the left normal of the chord
+    let offset = (1.0 - bulge * bulge) / (4.0 * bulge);
+    let centre = Point::new(
+        (stroke.x0 + stroke.x1) / 2.0 - offset * dy,
+        (stroke.y0 + stroke.y1) / 2.0 + offset * dx,
+    );
+    (centre, 4.0 * bulge.atan())
+}
+
+/// Point at parameter `t` in [0, 1] along the stroke.
+pub fn point_at(stroke: &Stroke, t: f64) -> Point {
+    let (p0, p1) = (start(stroke), end(stroke));
+    match stroke.kind {
+        StrokeKind::Arc { bulge } if bulge.abs() >= EPSILON => {
+            let (centre, angle) = arc_geometry(stroke, bulge);
+            let radius = centre.distance(&p0);
+            let start_angle = (p0.y - centre.y).atan2(p0.x - centre.x);
+            let theta = start_angle + t * angle;
+            Point::new(
+                centre.x + radius * theta.cos(),
+                centre.y + radius * theta.sin(),
+            )
+        }
+        StrokeKind::Quadratic { cx, cy } => {
+            let (a, b, c) = ((1.0 - t) * (1.0 - t), 2.0 * t * (1.0 - t), t * t);
+            Point::new(a * p0.x + b * cx + c * p1.x, a * p0.y + b * cy + c * p1.y)
+        }
+        StrokeKind::Cubic { cx0, cy0, cx1, cy1 } => {
+            let u = 1.0 - t;
+            let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+            Point::new(
+                a * p0.x + b * cx0 + c * cx1 + d * p1.x,
+                a * p0.y + b * cy0 + c * cy1 + d * p1.y,
+            )
+        }
+        _ => Point::new(p0.x + t * (p1.x - p0.x), p0.y + t * (p1.y - p0.y)),
+    }
+}
+
+/// Points of the polyline approximating the stroke, from start to end.
+pub fn flatten(stroke: &Stroke) -> Vec<Point> {
+    if stroke.kind.is_line() {
+        return vec![start(stroke), end(stroke)];
+    }
+    (0..=CURVE_SEGMENTS)
+        .map(|i| point_at(stroke, i as f64 / CURVE_SEGMENTS as f64))
+        .collect()
+}
+
+/// Straight strokes approximating the stroke.
+pub fn segments(stroke: &Stroke) -> Vec<Stroke> {
+    flatten(stroke)
+        .windows(2)
+        .map(|pair| Stroke::line(pair[0].x, pair[0].y, pair[1].x, pair[1].y))
+        .collect()
+}
+
+pub fn length(stroke: &Stroke) -> f64 {
+    flatten(stroke)
+        .windows(2)
+        .map(|pair| pair[0].distance(&pair[1]))
+        .sum()
+}
+
+/// Parameter of the orthogonal projection of `point` on the chord of the stroke, clamped to [0, 1].
+pub fn project(stroke: &Stroke, point: &Point) -> f64 {
+    let dx = stroke.x1 - stroke.x0;
+    let dy = stroke.y1 - stroke.y0;
+    let squared_length = dx * dx + dy * dy;
+    if squared_length < EPSILON {
+        return 0.0;
+    }
+    let t = ((point.x - stroke.x0) * dx + (point.y - stroke.y0) * dy) / squared_length;
+    t.clamp(0.0, 1.0)
+}
+
+fn point_segment_distance(point: &Point, segment: &Stroke) -> f64 {
+    let t = project(segment, point);
+    Point::new(
+        segment.x0 + t * (segment.x1 - segment.x0),
+        segment.y0 + t * (segment.y1 - segment.y0),
+    )
+    .distance(point)
+}
+
+pub fn point_stroke_distance(point: &Point, stroke: &Stroke) -> f64 {
+    segments(stroke)
+        .iter()
+        .map(|segment| point_segment_distance(point, segment))
+        .fold(f64::INFINITY, f64::min)
+}
+
+pub fn is_on_stroke(point: &Point, stroke: &Stroke) -> bool {
+    point_stroke_distance(point, stroke) < EPSILON
+}
+
+fn cross(ax: f64, ay: f64, bx: f64, by: f64) -> f64 {
+    ax * by - ay * bx
+}
+
+fn segment_intersections(a: &Stroke, b: &Stroke) -> Vec<Point> {
+    let (rx, ry) = (a.x1 - a.x0, a.y1 - a.y0);
+    let (sx, sy) = (b.x1 - b.x0, b.y1 - b.y0);
+    let (qx, qy) = (b.x0 - a.x0, b.y0 - a.y0);
+    let denominator = cross(rx, ry, sx, sy);
+
+    if denominator.abs() < EPSILON {
+        if cross(qx, qy, rx, ry).abs() >= EPSILON {
+            // Parallel, distinct lines
+            return vec![];
+        }
+        // Collinear: keep the endpoints lying on the other stroke
+        let mut points: Vec<Point> = Vec::new();
+        for point in [start(a), end(a), start(b), end(b)] {
+            let other_on = is_on_stroke(&point, a) && is_on_stroke(&point, b);
+            if other_on && points.iter().all(|p| p.key() != point.key()) {
+                points.push(point);
+            }
+        }
+        return points;
+    }
+
+    let t = cross(qx, qy, sx, sy) / denominator;
+    let u = cross(qx, qy, rx, ry) / denominator;
+    if (-EPSILON..=1.0 + EPSILON).contains(&t) && (-EPSILON..=1.0 + EPSILON).contains(&u) {
+        vec![point_at(a, t.clamp(0.0, 1.0))]
+    } else {
+        vec![]
+    }
+}
+
+/// Points shared by two strokes. Straight strokes share none, a single crossing or contact
+/// point, or the two ends of the common part when they overlap along the same line; curved
+/// strokes are intersected through their polyline approximation.
+pub fn stroke_intersections(a: &Stroke, b: &Stroke) -> Vec<Point> {
+    if a.kind.is_line() && b.kind.is_line() {
+        return segment_intersections(a, b);
+    }
+    let mut points: Vec<Point> = Vec::new();
+    for sa in segments(a) {
+        for sb in segments(b) {
+            for point in segment_intersections(&sa, &sb) {
+                if points.iter().all(|p| p.key() != point.key()) {
+                    points.push(point);
+                }
+            }
+        }
+    }
+    points
+}
+
+/// Whether two strokes share a piece of positive length, compared through their polyline
+/// approximations.
+pub fn strokes_overlap(a: &Stroke, b: &Stroke) -> bool {
+    let (sa, sb) = (segments(a), segments(b));
+    sa.iter().any(|x| {
+        sb.iter().any(|y| {
+            let points = segment_intersections(x, y);
+            points.len() > 1 && points[0].distance(&points[1]) >= EPSILON
+        })
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    pub fn from_strokes(strokes: &[Stroke]) -> Option<Self> {
+        let mut points = strokes.iter().flat_map(flatten);
+        let first = points.next()?;
+        Some(points.fold(
+            BoundingBox {
+                min: first,
+                max: first,
+            },
+            |bbox, p| BoundingBox {
+                min: Point::new(bbox.min.x.min(p.x), bbox.min.y.min(p.y)),
+                max: Point::new(bbox.max.x.max(p.x), bbox.max.y.max(p.y)),
+            },
+        ))
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    pub fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+}
+
+/// Planar graph of a drawing: vertices are stroke endpoints and intersection points,
+/// edges are the pieces of strokes between consecutive vertices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanarGraph {
+    pub vertices: Vec<Point>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl PlanarGraph {
+    pub fn from_strokes(strokes: &[Stroke]) -> Self {
+        let strokes: Vec<Stroke> = strokes.iter().flat_map(segments).collect();
+        let mut points: Vec<Point> = Vec::new();
+        for (i, a) in strokes.iter().enumerate() {
+            points.push(start(a));
+            points.push(end(a));
+            for b in strokes.iter().skip(i + 1) {
+                points.extend(stroke_intersections(a, b));
+            }
+        }
+
+        let mut unique: HashMap<(i64, i64), Point> = HashMap::new();
+        for point in points {
+            unique.entry(point.key()).or_insert(point);
+        }
+        let mut vertices: Vec<Point> = unique.into_values().collect();
+        vertices.sort_by_key(|p| p.key());
+        let index: HashMap<(i64, i64), usize> = vertices
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p.key(), i))
+            .collect();
+
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        for stroke in &strokes {
+            let mut on_stroke: Vec<(f64, usize)> = vertices
+                .iter()
+                .filter(|p| is_on_stroke(p, stroke))
+                .map(|p| (project(stroke, p), index[&p.key()]))
+                .collect();
+            on_stroke.sort_by(|a, b| a.0.total_cmp(&b.0));
+            for pair in on_stroke.windows(2) {
+                let (i, j) = (pair[0].1, pair[1].1);
+                let edge = (i.min(j), i.max(j));
+                if i != j && !edges.contains(&edge) {
+                    edges.push(edge);
+                }
+            }
+        }
+        edges.sort();
+
+        PlanarGraph { vertices, edges }
+    }
+
+    pub fn degree(&self, vertex: usize) -> usize {
+        self.edges
+            .iter()
+            .filter(|(i, j)| *i == vertex || *j == vertex)
+            .count()
+    }
+
+    /// Component label of each vertex, labels being the smallest vertex index of the component.
+    pub fn components(&self) -> Vec<usize> {
+        let mut parent: Vec<usize> = (0..self.vertices.len()).collect();
+        fn find(parent: &mut Vec<usize>, i: usize) -> usize {
+            if parent[i] != i {
+                let root = find(parent, parent[i]);
+                parent[i] = root;
+            }
+            parent[i]
+        }
+        for &(i, j) in &self.edges {
+            let (ri, rj) = (find(&mut parent, i), find(&mut parent, j));
+            if ri != rj {
+                parent[ri.max(rj)] = ri.min(rj);
+            }
+        }
+        (0..self.vertices.len())
+            .map(|i| find(&mut parent, i))
+            .collect()
+    }
+
+    pub fn connected_components(&self) -> usize {
+        self.components()
+            .iter()
+            .enumerate()
+            .filter(|&(i, &label)| i == label)
+            .count()
+    }
+
+    /// Number of bounded faces, from Euler's formula V - E + F = 1 + C.
+    pub fn enclosed_regions(&self) -> usize {
+        (self.edges.len() + self.connected_components()).saturating_sub(self.vertices.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stroke(x0: f64, y0: f64, x1: f64, y1: f64) -> Stroke {
+        Stroke::line(x0, y0, x1, y1)
+    }
+
+    #[test]
+    fn test_stroke_intersections_crossing() {
+        let points =
+            stroke_intersections(&stroke(-1.0, -1.0, 1.0, 1.0), &stroke(-1.0, 1.0, 1.0, -1.0));
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].key(), Point::new(0.0, 0.0).key());
+    }
+
+    #[test]
+    fn test_stroke_intersections_parallel() {
+        let points =
+            stroke_intersections(&stroke(-1.0, -1.0, -1.0, 1.0), &stroke(1.0, -1.0, 1.0, 1.0));
+        assert!(points.is_empty());
+    }
+
+    #[test]
+    fn test_stroke_intersections_overlap() {
+        let points =
+            stroke_intersections(&stroke(-1.0, -1.0, 1.0, 1.0), &stroke(0.0, 0.0, 1.0, 1.0));
+        assert_eq!(points.len(), 2);
+        assert!(strokes_overlap(
+            &stroke(-1.0, -1.0, 1.0, 1.0),
+            &stroke(0.0, 0.0, 1.0, 1.0)
+        ));
+        assert!(!strokes_overlap(
+            &stroke(-1.0, -1.0, 0.0, 0.0),
+            &stroke(0.0, 0.0, 1.0, 1.0)
+        ));
+    }
+
+    #[test]
+    fn test_planar_graph_cross() {
+        let graph = PlanarGraph::from_strokes(&[
+            stroke(-1.0, -1.0, 1.0, 1.0),
+            stroke(-1.0, 1.0, 1.0, -1.0),
+        ]);
+        assert_eq!(graph.vertices.len(), 5);
+        assert_eq!(graph.edges.len(), 4);
+        assert_eq!(graph.connected_components(), 1);
+        assert_eq!(graph.enclosed_regions(), 0);
+    }
+
+    #[test]
+    fn test_planar_graph_box() {
+        let graph = PlanarGraph::from_strokes(&[
+            stroke(-1.0, -1.0, -1.0, 1.0),
+            stroke(-1.0, 1.0, 1.0, 1.0),
+            stroke(1.0, 1.0, 1.0, -1.0),
+            stroke(1.0, -1.0, -1.0, -1.0),
+            stroke(-1.0, -1.0, 1.0, 1.0),
+        ]);
+        assert_eq!(graph.vertices.len(), 4);
+        assert_eq!(graph.edges.len(), 5);
+        assert_eq!(graph.enclosed_regions(), 2);
+    }
+
+    #[test]
+    fn test_arc_point_at() {
+        // Quarter circle around the origin, counterclockwise
+        let arc = Stroke::arc(1.0, 0.0, 0.0, 1.0, (std::f64::consts::PI / 8.0).tan());
+        let middle = point_at(&arc, 0.5);
+        let expected = std::f64::consts::FRAC_1_SQRT_2;
+        assert!((middle.x - expected).abs() < 1e-9);
+        assert!((middle.y - expected).abs() < 1e-9);
+        assert!((length(&arc) - std::f64::consts::FRAC_PI_2).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_curve_intersections() {
+        let curve = Stroke::quadratic(-1.0, -1.0, 0.0, 2.0, 1.0, -1.0);
+        let line = stroke(-1.0, 0.0, 1.0, 0.0);
+        assert_eq!(stroke_intersections(&curve, &line).len(), 2);
+
+        // Semicircle below the chord only touches the chord at its ends
+        let semicircle = Stroke::arc(-1.0, 0.0, 1.0, 0.0, 1.0);
+        assert!(is_on_stroke(&Point::new(0.0, -1.0), &semicircle));
+        assert_eq!(stroke_intersections(&semicircle, &line).len(), 2);
+    }
+
+    #[test]
+    fn test_planar_graph_curve() {
+        // A straight chord closed by an arc encloses one region
+        let graph = PlanarGraph::from_strokes(&[
+            stroke(-1.0, 0.0, 1.0, 0.0),
+            Stroke::arc(-1.0, 0.0, 1.0, 0.0, 1.0),
+        ]);
+        assert_eq!(graph.connected_components(), 1);
+        assert_eq!(graph.enclosed_regions(), 1);
+    }
+}
@@ -5,6 +5,7 @@ use crate::stroke::Stroke;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
 pub struct Glyph {
     pub strokes: Vec<Stroke>,
 }
@@ -20,6 +21,20 @@ impl PartialEq for Glyph {
 
 impl Eq for Glyph {}
 
+/// Number of parent strokes glyph identifiers, one bit per stroke, can tell apart.
+pub const MAX_PARENT_STROKES: usize = u64::BITS as usize;
+
+/// Indices of the set bits of `mask`, in increasing order.
+pub(crate) fn set_bits(mut mask: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        (mask != 0).then(|| {
+            let index = mask.trailing_zeros() as usize;
+            mask &= mask - 1;
+            index
+        })
+    })
+}
+
 #[derive(Debug, Clone)]
 pub struct InternalGlyph {
     pub strokes: Vec<InternalStroke>,
@@ -35,6 +50,11 @@ impl InternalGlyph {
     }
 
     pub fn from_stroke(stroke: InternalStroke) -> Self {
+        assert!(
+            stroke.index < MAX_PARENT_STROKES,
+            "Stroke {} does not fit glyph identifiers",
+            stroke.index
+        );
         let identifier = 1 << stroke.index;
         InternalGlyph {
             strokes: vec![stroke],
@@ -42,25 +62,19 @@ impl InternalGlyph {
         }
     }
 
-    pub fn union(&self, other: &Self) -> Self {
-        let mut indices: Vec<usize> = self.strokes.iter().map(|s| s.index).collect();
-        indices.extend(other.strokes.iter().map(|s| s.index));
-
-        let unique_indices: Vec<usize> = indices
-            .into_iter()
-            .collect::<std::collections::HashSet<_>>()
-            .into_iter()
-            .collect();
-        let new_strokes: Vec<InternalStroke> = unique_indices
-            .into_iter()
-            .map(|index| InternalStroke { index })
-            .collect();
-
+    pub fn from_identifier(identifier: u64) -> Self {
         InternalGlyph {
-            strokes: new_strokes,
-            identifier: self.identifier | other.identifier,
+            strokes: set_bits(identifier)
+                .map(|index| InternalStroke { index })
+                .collect(),
+            identifier,
         }
     }
+
+    /// Strokes of both glyphs, sorted by index.
+    pub fn union(&self, other: &Self) -> Self {
+        InternalGlyph::from_identifier(self.identifier | other.identifier)
+    }
 }
 
 impl PartialEq for InternalGlyph {
@@ -70,3 +84,22 @@ impl PartialEq for InternalGlyph {
 }
 
 impl Eq for InternalGlyph {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glyph_union() {
+        let a = InternalGlyph::from_identifier(0b1010);
+        let b = InternalGlyph::from_stroke(InternalStroke { index: 63 });
+        let union = b.union(&a);
+        assert_eq!(union.identifier, 0b1010 | 1 << 63);
+        let indices: Vec<usize> = union.strokes.iter().map(|s| s.index).collect();
+        assert_eq!(indices, vec![1, 3, 63]);
+        assert!(InternalGlyph::empty()
+            .union(&InternalGlyph::empty())
+            .strokes
+            .is_empty());
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::geometry::Point;
+use crate::layout::{
+    detect_symmetries, find_stroke, intersection_matrix, symmetry_group_name,
+    transformation_matrix, Isometry,
+};
+use crate::parameters::Parameters;
+use crate::stroke::Stroke;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub version: u32,
+    pub anchors: Vec<Point>,
+    pub strokes: usize,
+    pub curved_strokes: usize,
+    /// Group of the anchor symmetries mapping the strokes onto themselves, e.g. "D4".
+    pub symmetry: String,
+    pub transformations: usize,
+    /// Share of distinct stroke pairs marked as intersecting.
+    pub intersection_density: f64,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "version: {}", self.version)?;
+        writeln!(f, "anchors: {}", self.anchors.len())?;
+        for (i, anchor) in self.anchors.iter().enumerate() {
+            writeln!(f, "  {i}: ({}, {})", anchor.x, anchor.y)?;
+        }
+        writeln!(
+            f,
+            "strokes: {} ({} curved)",
+            self.strokes, self.curved_strokes
+        )?;
+        writeln!(
+            f,
+            "symmetry: {} ({} transformations in the file)",
+            self.symmetry, self.transformations
+        )?;
+        writeln!(f, "intersection density: {:.3}", self.intersection_density)
+    }
+}
+
+/// Stroke-by-stroke comparison of two parameter sets, strokes matched by geometry.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParametersDiff {
+    /// Index and stroke of the strokes of the first set missing from the second.
+    pub removed: Vec<(usize, Stroke)>,
+    /// Index and stroke of the strokes of the second set missing from the first.
+    pub added: Vec<(usize, Stroke)>,
+    /// Indices in both sets of the common strokes whose index changed.
+    pub moved: Vec<(usize, usize)>,
+    /// Pairs of common strokes, as indices in the first set, intersecting in only one set.
+    pub intersections: Vec<(usize, usize)>,
+    pub transformations: (usize, usize),
+}
+
+impl ParametersDiff {
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+            && self.added.is_empty()
+            && self.moved.is_empty()
+            && self.intersections.is_empty()
+            && self.transformations.0 == self.transformations.1
+    }
+}
+
+impl fmt::Display for ParametersDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no differences");
+        }
+        for (i, s) in &self.removed {
+            writeln!(
+                f,
+                "- stroke {i}: ({}, {}) -> ({}, {})",
+                s.x0, s.y0, s.x1, s.y1
+            )?;
+        }
+        for (i, s) in &self.added {
+            writeln!(
+                f,
+                "+ stroke {i}: ({}, {}) -> ({}, {})",
+                s.x0, s.y0, s.x1, s.y1
+            )?;
+        }
+        for (i, j) in &self.moved {
+            writeln!(f, "~ stroke {i} is now stroke {j}")?;
+        }
+        for (i, j) in &self.intersections {
+            writeln!(f, "~ intersection of strokes {i} and {j}")?;
+        }
+        if self.transformations.0 != self.transformations.1 {
+            writeln!(
+                f,
+                "~ transformations: {} -> {}",
+                self.transformations.0, self.transformations.1
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Differences between the matrices of a parameter set and the ones its geometry gives.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Drift {
+    /// Distinct stroke pairs whose intersection entry disagrees with the geometry.
+    pub intersections: Vec<(usize, usize)>,
+    /// Transformations of the geometry missing from the transformation matrix.
+    pub missing_transformations: Vec<Vec<usize>>,
+    /// Columns of the transformation matrix that are not symmetries of the geometry.
+    pub unexpected_transformations: Vec<Vec<usize>>,
+}
+
+impl Drift {
+    pub fn is_empty(&self) -> bool {
+        self.intersections.is_empty()
+            && self.missing_transformations.is_empty()
+            && self.unexpected_transformations.is_empty()
+    }
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "matrices match the geometry");
+        }
+        for (i, j) in &self.intersections {
+            writeln!(
+                f,
+                "intersection of strokes {i} and {j} disagrees with the geometry"
+            )?;
+        }
+        for column in &self.missing_transformations {
+            writeln!(f, "missing transformation {column:?}")?;
+        }
+        for column in &self.unexpected_transformations {
+            writeln!(f, "unexpected transformation {column:?}")?;
+        }
+        Ok(())
+    }
+}
+
+fn columns(matrix: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let n = matrix.first().map_or(0, |row| row.len());
+    (0..n)
+        .map(|j| matrix.iter().map(|row| row[j]).collect())
+        .collect()
+}
+
+impl Parameters {
+    /// Symmetries of the anchors mapping every parent stroke onto a parent stroke.
+    pub fn symmetries(&self) -> Vec<Isometry> {
+        let strokes = &self.parent_strokes;
+        detect_symmetries(&self.anchors())
+            .into_iter()
+            .filter(|symmetry| {
+                strokes
+                    .iter()
+                    .all(|s| find_stroke(strokes, &symmetry.apply_stroke(s)).is_some())
+            })
+            .collect()
+    }
+
+    pub fn summary(&self) -> Summary {
+        let n = self.parent_strokes.len();
+        let intersecting = (0..n)
+            .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+            .filter(|&(i, j)| self.intersection_matrix[i][j] != 0)
+            .count();
+        let pairs = n * n.saturating_sub(1) / 2;
+        Summary {
+            version: self.version,
+            anchors: self.anchors(),
+            strokes: n,
+            curved_strokes: self
+                .parent_strokes
+                .iter()
+                .filter(|s| !s.kind.is_line())
+                .count(),
+            symmetry: symmetry_group_name(&self.symmetries()),
+            transformations: self
+                .transformation_matrix
+                .first()
+                .map_or(0, |row| row.len()),
+            intersection_density: if pairs == 0 {
+                0.0
+            } else {
+                intersecting as f64 / pairs as f64
+            },
+        }
+    }
+
+    pub fn diff(&self, other: &Parameters) -> ParametersDiff {
+        let matches: Vec<Option<usize>> = self
+            .parent_strokes
+            .iter()
+            .map(|s| find_stroke(&other.parent_strokes, s))
+            .collect();
+        let matched: HashSet<usize> = matches.iter().flatten().copied().collect();
+
+        let mut diff = ParametersDiff {
+            transformations: (
+                self.transformation_matrix
+                    .first()
+                    .map_or(0, |row| row.len()),
+                other
+                    .transformation_matrix
+                    .first()
+                    .map_or(0, |row| row.len()),
+            ),
+            ..ParametersDiff::default()
+        };
+        for (i, m) in matches.iter().enumerate() {
+            match m {
+                None => diff.removed.push((i, self.parent_strokes[i])),
+                Some(j) if *j != i => diff.moved.push((i, *j)),
+                _ => {}
+            }
+        }
+        for (j, stroke) in other.parent_strokes.iter().enumerate() {
+            if !matched.contains(&j) {
+                diff.added.push((j, *stroke));
+            }
+        }
+        for (i, mi) in matches.iter().enumerate() {
+            for (k, mk) in matches.iter().enumerate().skip(i + 1) {
+                if let (Some(a), Some(b)) = (mi, mk) {
+                    let left = self.intersection_matrix[i][k] != 0;
+                    let right = other.intersection_matrix[*a][*b] != 0;
+                    if left != right {
+                        diff.intersections.push((i, k));
+                    }
+                }
+            }
+        }
+        diff
+    }
+
+    /// Same strokes, with the matrices computed from their geometry.
+    pub fn regenerate(&self) -> Parameters {
+        let strokes = &self.parent_strokes;
+        Parameters {
+            version: self.version,
+            parent_strokes: strokes.clone(),
+            intersection_matrix: intersection_matrix(strokes),
+            transformation_matrix: transformation_matrix(strokes, &self.symmetries())
+                .expect("Symmetries were filtered to map strokes onto strokes"),
+        }
+    }
+
+    /// Disagreements between the matrices and the geometry. The diagonal of the intersection
+    /// matrix and the order of the transformations are not significant and are ignored.
+    pub fn drift(&self) -> Drift {
+        let regenerated = self.regenerate();
+        let n = self.parent_strokes.len();
+        let intersections = (0..n)
+            .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+            .filter(|&(i, j)| {
+                (self.intersection_matrix[i][j] != 0)
+                    != (regenerated.intersection_matrix[i][j] != 0)
+            })
+            .collect();
+        let expected = columns(&regenerated.transformation_matrix);
+        let actual = columns(&self.transformation_matrix);
+        Drift {
+            intersections,
+            missing_transformations: expected
+                .iter()
+                .filter(|c| !actual.contains(c))
+                .cloned()
+                .collect(),
+            unexpected_transformations: actual
+                .iter()
+                .filter(|c| !expected.contains(c))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Layout;
+    use crate::tests::setup;
+
+    #[test]
+    fn test_summary() {
+        let summary = Parameters::from_layout(&Layout::Square { size: 3 })
+            .unwrap()
+            .summary();
+        assert_eq!(summary.anchors.len(), 9);
+        assert_eq!(summary.strokes, 36);
+        assert_eq!(summary.symmetry, "D4");
+        assert_eq!(summary.transformations, 7);
+        assert!(summary.intersection_density > 0.0 && summary.intersection_density < 1.0);
+    }
+
+    #[test]
+    fn test_diff() {
+        let a = Parameters::from_layout(&Layout::Square { size: 2 }).unwrap();
+        assert!(a.diff(&a).is_empty());
+
+        let mut b = a.clone();
+        b.parent_strokes.swap(0, 1);
+        b.parent_strokes[2] = b.parent_strokes[2].reversed();
+        b.parent_strokes.pop();
+        let b = b.regenerate();
+        let diff = a.diff(&b);
+        assert_eq!(diff.removed, vec![(5, a.parent_strokes[5])]);
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.moved, vec![(0, 1), (1, 0)]);
+        assert!(diff.intersections.is_empty());
+    }
+
+    #[test]
+    fn test_drift() {
+        let parameters = Parameters::from_layout(&Layout::Square { size: 2 }).unwrap();
+        assert!(parameters.drift().is_empty());
+
+        let mut edited = parameters.clone();
+        edited.intersection_matrix[0][5] = 1 - edited.intersection_matrix[0][5];
+        edited.transformation_matrix.iter_mut().for_each(|row| {
+            row.pop();
+        });
+        let drift = edited.drift();
+        assert_eq!(drift.intersections, vec![(0, 5)]);
+        assert_eq!(drift.missing_transformations.len(), 1);
+        assert!(drift.unexpected_transformations.is_empty());
+    }
+
+    #[test]
+    fn test_shipped_parameters_drift() {
+        let context = setup();
+        for generator in [context.basic, context.advanced] {
+            let parameters = &generator.parameters;
+            assert!(parameters.drift().is_empty(), "{}", parameters.drift());
+            assert!(parameters.diff(&parameters.regenerate()).is_empty());
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-fn dfs(node: usize, visited: &mut HashSet<usize>, subgraph: &Vec<Vec<u8>>) {
+fn dfs(node: usize, visited: &mut HashSet<usize>, subgraph: &[Vec<u8>]) {
     visited.insert(node);
     for (neighbor, &is_connected) in subgraph[node].iter().enumerate() {
         if is_connected == 1 && !visited.contains(&neighbor) {
@@ -9,7 +9,7 @@ fn dfs(node: usize, visited: &mut HashSet<usize>, subgraph: &Vec<Vec<u8>>) {
     }
 }
 
-pub fn are_strokes_linked(strokes: &Vec<usize>, intersection_matrix: &Vec<Vec<u8>>) -> bool {
+pub fn are_strokes_linked(strokes: &[usize], intersection_matrix: &[Vec<u8>]) -> bool {
     if strokes.is_empty() {
         return false;
     }
@@ -34,30 +34,24 @@ mod tests {
 
     #[test]
     fn test_are_strokes_linked_single() {
-        assert!(are_strokes_linked(&vec![0], &vec![vec![1]]));
+        assert!(are_strokes_linked(&[0], &[vec![1]]));
     }
 
     #[test]
     fn test_are_strokes_linked_pair_connected() {
-        assert!(are_strokes_linked(
-            &vec![0, 1],
-            &vec![vec![1, 1], vec![1, 1]]
-        ));
+        assert!(are_strokes_linked(&[0, 1], &[vec![1, 1], vec![1, 1]]));
     }
 
     #[test]
     fn test_are_strokes_linked_pair_disconnected() {
-        assert!(!are_strokes_linked(
-            &vec![0, 1],
-            &vec![vec![1, 0], vec![0, 1]]
-        ));
+        assert!(!are_strokes_linked(&[0, 1], &[vec![1, 0], vec![0, 1]]));
     }
 
     #[test]
     fn test_are_strokes_linked_group_disconnected() {
         assert!(!are_strokes_linked(
-            &vec![0, 1, 2, 3],
-            &vec![
+            &[0, 1, 2, 3],
+            &[
                 vec![1, 1, 0, 0],
                 vec![1, 1, 0, 0],
                 vec![0, 0, 1, 1],
@@ -69,8 +63,8 @@ mod tests {
     #[test]
     fn test_are_strokes_linked_specific_case() {
         assert!(!are_strokes_linked(
-            &vec![0, 3],
-            &vec![
+            &[0, 3],
+            &[
                 vec![1, 1, 1, 0],
                 vec![1, 1, 0, 1],
                 vec![1, 0, 1, 1],
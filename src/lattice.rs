@@ -0,0 +1,269 @@
+use crate::constraints::Constraints;
+use crate::generate::{GlyphGenerator, ResolveError};
+use crate::glyph::{Glyph, InternalGlyph};
+use crate::stroke::Stroke;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatticeNode {
+    pub id: usize,
+    /// Number of strokes added to the seed.
+    pub level: usize,
+    /// Smallest identifier over the symmetry class of the glyph.
+    pub identifier: u64,
+    /// Member of the class with the smallest identifier, among those meeting the constraints.
+    pub glyph: Glyph,
+}
+
+/// Adding `stroke` to the glyph of node `from` gives the image of the glyph of node `to`
+/// under `transformation`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatticeEdge {
+    pub from: usize,
+    pub to: usize,
+    /// Index of the added stroke in the parent strokes.
+    pub stroke_index: usize,
+    pub stroke: Stroke,
+    /// Column of the transformation matrix, none for the identity.
+    pub transformation: Option<usize>,
+}
+
+/// Hasse diagram of the generation: one node per generated glyph, one edge per stroke
+/// extending a glyph into the next level.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lattice {
+    pub nodes: Vec<LatticeNode>,
+    pub edges: Vec<LatticeEdge>,
+}
+
+impl Lattice {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// GraphViz description, nodes labelled with their parent stroke indices and edges with
+    /// the added stroke index.
+    pub fn to_dot(&self, generator: &GlyphGenerator) -> String {
+        let mut dot = String::from("digraph lattice {\n    rankdir=TB;\n");
+        for node in &self.nodes {
+            let mut indices: Vec<usize> = generator
+                .from_glyph(&node.glyph)
+                .strokes
+                .iter()
+                .map(|s| s.index)
+                .collect();
+            indices.sort();
+            let indices: Vec<String> = indices.iter().map(|i| i.to_string()).collect();
+            dot.push_str(&format!(
+                "    n{} [label=\"{{{}}}\"];\n",
+                node.id,
+                indices.join(",")
+            ));
+        }
+        for edge in &self.edges {
+            dot.push_str(&format!(
+                "    n{} -> n{} [label=\"{}\"];\n",
+                edge.from, edge.to, edge.stroke_index
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl GlyphGenerator {
+    /// Same glyphs as `generate`, in the same order, together with the extension edges.
+    pub fn generate_lattice(&self, strokes: &[Stroke], seed: &Stroke) -> Lattice {
+        self.generate_lattice_constrained(strokes, seed, &Constraints::default())
+            .unwrap()
+    }
+
+    /// Same glyphs as `generate_constrained`, up to symmetry and in the same order, together
+    /// with the extension edges between them.
+    pub fn generate_lattice_constrained(
+        &self,
+        strokes: &[Stroke],
+        seed: &Stroke,
+        constraints: &Constraints,
+    ) -> Result<Lattice, ResolveError> {
+        let masks = self.constraint_masks(constraints)?;
+        let stroke_indices: Vec<usize> = strokes
+            .iter()
+            .map(|stroke| self.from_stroke(stroke).strokes[0].index)
+            .collect();
+        let mut raw_edges: Vec<(usize, usize, usize, usize)> = Vec::new();
+        let levels = self.generate_levels(strokes, seed, &masks, |level, from, stroke, to| {
+            raw_edges.push((level, from, stroke, to))
+        });
+        // Index of the stroke under the orbit position, identity first
+        let image = |index: usize, position: usize| match position {
+            0 => index,
+            _ => self.parameters.transformation_matrix[index][position - 1],
+        };
+
+        // Node id of each glyph of each level if it meets the constraints, with the orbit
+        // position of the node glyph
+        let mut ids: Vec<Vec<Option<(usize, usize)>>> = Vec::with_capacity(levels.len());
+        let mut nodes: Vec<LatticeNode> = Vec::new();
+        for (level, glyphs) in levels.iter().enumerate() {
+            let mut level_ids = Vec::with_capacity(glyphs.len());
+            for glyph in glyphs {
+                let orbit = self.orbit_identifiers(glyph);
+                let shown = orbit
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &identifier)| masks.accepts(identifier))
+                    .min_by_key(|(_, &identifier)| identifier);
+                level_ids.push(shown.map(|(position, &identifier)| {
+                    nodes.push(LatticeNode {
+                        id: nodes.len(),
+                        level,
+                        identifier: *orbit.iter().min().unwrap(),
+                        glyph: self.to_glyph(&InternalGlyph::from_identifier(identifier)),
+                    });
+                    (nodes.len() - 1, position)
+                }));
+            }
+            ids.push(level_ids);
+        }
+
+        let edges = raw_edges
+            .into_iter()
+            .filter_map(|(level, from, stroke, to)| {
+                let (from_id, from_position) = ids[level][from]?;
+                let (to_id, _) = ids[level + 1][to]?;
+                // Move the extension onto the glyph shown by `from`
+                let stroke_index = image(stroke_indices[stroke], from_position);
+                let from_glyph = self.from_glyph(&nodes[from_id].glyph);
+                let extended = from_glyph.union(&InternalGlyph::from_identifier(1 << stroke_index));
+                let to_glyph = self.from_glyph(&nodes[to_id].glyph);
+                let position = self
+                    .orbit_identifiers(&to_glyph)
+                    .iter()
+                    .position(|&identifier| identifier == extended.identifier)
+                    .unwrap();
+                Some(LatticeEdge {
+                    from: from_id,
+                    to: to_id,
+                    stroke_index,
+                    stroke: self.parameters.parent_strokes[stroke_index],
+                    transformation: position.checked_sub(1),
+                })
+            })
+            .collect();
+
+        Ok(Lattice { nodes, edges })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::Parameters;
+    use crate::stroke::StrokeRef;
+
+    fn generator() -> GlyphGenerator {
+        let parameters: Parameters =
+            serde_json::from_str(include_str!("../tests/parameters_4ap.json")).unwrap();
+        GlyphGenerator::new(parameters)
+    }
+
+    /// Identifier of the image of the glyph of `to` under the edge transformation.
+    fn edge_image(generator: &GlyphGenerator, lattice: &Lattice, edge: &LatticeEdge) -> u64 {
+        let to = generator.from_glyph(&lattice.nodes[edge.to].glyph);
+        generator.orbit_identifiers(&to)[edge.transformation.map_or(0, |t| t + 1)]
+    }
+
+    /// Identifier of the glyph of `from` with the edge stroke added.
+    fn extension(generator: &GlyphGenerator, lattice: &Lattice, edge: &LatticeEdge) -> u64 {
+        generator
+            .from_glyph(&lattice.nodes[edge.from].glyph)
+            .identifier
+            | 1 << edge.stroke_index
+    }
+
+    #[test]
+    fn test_lattice_matches_generate() {
+        let generator = generator();
+        let strokes = generator.parameters.parent_strokes.clone();
+        let lattice = generator.generate_lattice(&strokes, &strokes[0]);
+        let glyphs = generator.generate(&strokes, &strokes[0]);
+
+        assert_eq!(lattice.nodes.len(), glyphs.len());
+        for (node, glyph) in lattice.nodes.iter().zip(&glyphs) {
+            let canonical = generator.canonical_identifier(&generator.from_glyph(glyph));
+            assert_eq!(node.identifier, canonical);
+            assert_eq!(generator.from_glyph(&node.glyph).identifier, canonical);
+            assert_eq!(node.level + 1, glyph.strokes.len());
+        }
+        for edge in &lattice.edges {
+            let (from, to) = (&lattice.nodes[edge.from], &lattice.nodes[edge.to]);
+            assert_eq!(from.level + 1, to.level);
+            assert!(!from.glyph.strokes.contains(&edge.stroke));
+            assert_eq!(
+                edge_image(&generator, &lattice, edge),
+                extension(&generator, &lattice, edge)
+            );
+        }
+        assert!(lattice.edges.iter().any(|e| e.transformation.is_some()));
+        // Every glyph but the seed is reached from the previous level
+        for node in lattice.nodes.iter().skip(1) {
+            assert!(lattice.edges.iter().any(|e| e.to == node.id));
+        }
+    }
+
+    #[test]
+    fn test_constrained_lattice_matches_generate_constrained() {
+        let generator = generator();
+        let strokes = generator.parameters.parent_strokes.clone();
+        let constraints = Constraints {
+            required: vec![StrokeRef::Stroke(strokes[1])],
+            forbidden_sets: vec![vec![
+                StrokeRef::Stroke(strokes[2]),
+                StrokeRef::Stroke(strokes[3]),
+            ]],
+            ..Constraints::default()
+        };
+        let lattice = generator
+            .generate_lattice_constrained(&strokes, &strokes[0], &constraints)
+            .unwrap();
+        let glyphs = generator
+            .generate_constrained(&strokes, &strokes[0], &constraints)
+            .unwrap();
+
+        assert!(!glyphs.is_empty());
+        assert_eq!(lattice.nodes.len(), glyphs.len());
+        let masks = generator.constraint_masks(&constraints).unwrap();
+        for (node, glyph) in lattice.nodes.iter().zip(&glyphs) {
+            let shown = generator.from_glyph(&node.glyph);
+            assert!(masks.accepts(shown.identifier));
+            assert_eq!(
+                generator.canonical_identifier(&shown),
+                generator.canonical_identifier(&generator.from_glyph(glyph))
+            );
+        }
+        for edge in &lattice.edges {
+            let (from, to) = (&lattice.nodes[edge.from], &lattice.nodes[edge.to]);
+            assert_eq!(from.level + 1, to.level);
+            assert_eq!(
+                edge_image(&generator, &lattice, edge),
+                extension(&generator, &lattice, edge)
+            );
+        }
+    }
+
+    #[test]
+    fn test_lattice_exports() {
+        let generator = generator();
+        let strokes = generator.parameters.parent_strokes.clone();
+        let lattice = generator.generate_lattice(&strokes[..3], &strokes[0]);
+
+        let json: Lattice = serde_json::from_str(&lattice.to_json()).unwrap();
+        assert_eq!(json, lattice);
+
+        let dot = lattice.to_dot(&generator);
+        assert!(dot.starts_with("digraph lattice {"));
+        assert!(dot.contains("n0 [label=\"{0}\"];"));
+        assert_eq!(dot.matches("->").count(), lattice.edges.len());
+    }
+}
@@ -0,0 +1,503 @@
+use std::f64::consts::PI;
+use std::fmt;
+
+use crate::geometry::{stroke_intersections, Point, EPSILON};
+use crate::glyph::MAX_PARENT_STROKES;
+use crate::parameters::Parameters;
+use crate::schema::PARAMETERS_VERSION;
+use crate::stroke::{Stroke, DEFAULT_QUANTUM};
+use serde::{Deserialize, Serialize};
+
+/// Anchor layout, every pair of anchors making a parent stroke. Glyph identifiers hold 64
+/// strokes, so a layout has at most 11 anchors: square size 3, triangular rows 4, hexagonal
+/// radius 1, or circular count 11, 10 with the centre.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Layout {
+    /// `size` x `size` square grid, D4 symmetry.
+    Square { size: usize },
+    /// Triangle of the triangular lattice with `rows` anchors on each side, D3 symmetry.
+    Triangular { rows: usize },
+    /// Hexagon of the triangular lattice with `radius` anchors between the centre and a
+    /// corner, D6 symmetry.
+    Hexagonal { radius: usize },
+    /// `count` anchors evenly spaced on a circle, optionally with its centre, D`count` symmetry.
+    Circular { count: usize, centre: bool },
+    /// User-supplied anchors, symmetry being detected from their positions.
+    Custom { anchors: Vec<[f64; 2]> },
+}
+
+impl Layout {
+    /// Anchors centred on the origin and scaled to fit the [-1, 1] square.
+    pub fn anchors(&self) -> Vec<Point> {
+        let h = 3f64.sqrt() / 2.0;
+        let points: Vec<Point> = match self {
+            Layout::Square { size } => (0..*size)
+                .flat_map(|i| (0..*size).map(move |j| Point::new(j as f64, i as f64)))
+                .collect(),
+            Layout::Triangular { rows } => (0..*rows)
+                .flat_map(|i| {
+                    (0..=i).map(move |j| Point::new(j as f64 - i as f64 / 2.0, -(i as f64) * h))
+                })
+                .collect(),
+            Layout::Hexagonal { radius } => {
+                let radius = *radius as i64;
+                (-radius..=radius)
+                    .flat_map(|q| {
+                        (-radius..=radius)
+                            .filter(move |r| (q + r).abs() <= radius)
+                            .map(move |r| Point::new(q as f64 + r as f64 / 2.0, r as f64 * h))
+                    })
+                    .collect()
+            }
+            Layout::Circular { count, centre } => {
+                let mut points: Vec<Point> = (0..*count)
+                    .map(|k| {
+                        let angle = PI / 2.0 + 2.0 * PI * k as f64 / *count as f64;
+                        Point::new(angle.cos(), angle.sin())
+                    })
+                    .collect();
+                if *centre {
+                    points.push(Point::new(0.0, 0.0));
+                }
+                points
+            }
+            Layout::Custom { anchors } => anchors.iter().map(|&[x, y]| Point::new(x, y)).collect(),
+        };
+        normalize(&points)
+    }
+}
+
+fn normalize(points: &[Point]) -> Vec<Point> {
+    if points.is_empty() {
+        return vec![];
+    }
+    let centre = centroid(points);
+    let extent = points
+        .iter()
+        .map(|p| (p.x - centre.x).abs().max((p.y - centre.y).abs()))
+        .fold(0.0, f64::max);
+    let scale = if extent < EPSILON { 1.0 } else { 1.0 / extent };
+    let snap = |v: f64| if v.abs() < EPSILON { 0.0 } else { v };
+    points
+        .iter()
+        .map(|p| {
+            Point::new(
+                snap((p.x - centre.x) * scale),
+                snap((p.y - centre.y) * scale),
+            )
+        })
+        .collect()
+}
+
+fn centroid(points: &[Point]) -> Point {
+    let n = points.len() as f64;
+    Point::new(
+        points.iter().map(|p| p.x).sum::<f64>() / n,
+        points.iter().map(|p| p.y).sum::<f64>() / n,
+    )
+}
+
+/// Rotation or reflection fixing `centre`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Isometry {
+    pub centre: Point,
+    pub matrix: [[f64; 2]; 2],
+}
+
+impl Isometry {
+    pub fn rotation(centre: Point, angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Isometry {
+            centre,
+            matrix: [[cos, -sin], [sin, cos]],
+        }
+    }
+
+    /// Reflection across the line through `centre` making `axis` radians with the x axis.
+    pub fn reflection(centre: Point, axis: f64) -> Self {
+        let (sin, cos) = (2.0 * axis).sin_cos();
+        Isometry {
+            centre,
+            matrix: [[cos, sin], [sin, -cos]],
+        }
+    }
+
+    pub fn is_reflection(&self) -> bool {
+        self.matrix[0][0] * self.matrix[1][1] - self.matrix[0][1] * self.matrix[1][0] < 0.0
+    }
+
+    pub fn apply(&self, point: &Point) -> Point {
+        let (x, y) = (point.x - self.centre.x, point.y - self.centre.y);
+        Point::new(
+            self.centre.x + self.matrix[0][0] * x + self.matrix[0][1] * y,
+            self.centre.y + self.matrix[1][0] * x + self.matrix[1][1] * y,
+        )
+    }
+
+    pub fn apply_stroke(&self, stroke: &Stroke) -> Stroke {
+        stroke
+            .translated(-self.centre.x, -self.centre.y)
+            .transformed(self.matrix)
+            .translated(self.centre.x, self.centre.y)
+    }
+}
+
+fn find_point(points: &[Point], point: &Point) -> Option<usize> {
+    points
+        .iter()
+        .position(|p| p.distance(point) < DEFAULT_QUANTUM)
+}
+
+fn same_stroke(a: &Stroke, b: &Stroke) -> bool {
+    let close = |u: f64, v: f64| (u - v).abs() < DEFAULT_QUANTUM;
+    close(a.x0, b.x0)
+        && close(a.y0, b.y0)
+        && close(a.x1, b.x1)
+        && close(a.y1, b.y1)
+        && a.kind.tag() == b.kind.tag()
+        && a.kind
+            .values()
+            .iter()
+            .zip(b.kind.values())
+            .all(|(&u, v)| close(u, v))
+}
+
+/// Index of the stroke drawing the same thing as `stroke`, in either direction.
+pub fn find_stroke(strokes: &[Stroke], stroke: &Stroke) -> Option<usize> {
+    let reversed = stroke.reversed();
+    strokes
+        .iter()
+        .position(|s| same_stroke(s, stroke) || same_stroke(s, &reversed))
+}
+
+/// Non-identity isometries mapping the anchors onto themselves, rotations by increasing
+/// angle first, then reflections by increasing axis angle.
+pub fn detect_symmetries(anchors: &[Point]) -> Vec<Isometry> {
+    if anchors.len() < 2 {
+        return vec![];
+    }
+    let centre = centroid(anchors);
+    let radius = |p: &Point| p.distance(&centre);
+    let angle = |p: &Point| (p.y - centre.y).atan2(p.x - centre.x);
+    let reference = anchors
+        .iter()
+        .max_by(|a, b| radius(a).total_cmp(&radius(b)))
+        .unwrap();
+    if radius(reference) < DEFAULT_QUANTUM {
+        return vec![];
+    }
+
+    // Any symmetry maps the reference anchor to an anchor at the same distance from the
+    // centre, which determines the rotation or the reflection
+    let mut rotations: Vec<(f64, Isometry)> = Vec::new();
+    let mut reflections: Vec<(f64, Isometry)> = Vec::new();
+    for target in anchors
+        .iter()
+        .filter(|p| (radius(p) - radius(reference)).abs() < DEFAULT_QUANTUM)
+    {
+        let rotation_angle = (angle(target) - angle(reference)).rem_euclid(2.0 * PI);
+        if rotation_angle > DEFAULT_QUANTUM && rotation_angle < 2.0 * PI - DEFAULT_QUANTUM {
+            rotations.push((rotation_angle, Isometry::rotation(centre, rotation_angle)));
+        }
+        let axis = ((angle(target) + angle(reference)) / 2.0).rem_euclid(PI);
+        reflections.push((axis, Isometry::reflection(centre, axis)));
+    }
+
+    let preserves = |isometry: &Isometry| {
+        anchors
+            .iter()
+            .all(|p| find_point(anchors, &isometry.apply(p)).is_some())
+    };
+    rotations.retain(|(_, isometry)| preserves(isometry));
+    reflections.retain(|(_, isometry)| preserves(isometry));
+    rotations.sort_by(|a, b| a.0.total_cmp(&b.0));
+    reflections.sort_by(|a, b| a.0.total_cmp(&b.0));
+    rotations
+        .into_iter()
+        .chain(reflections)
+        .map(|(_, isometry)| isometry)
+        .collect()
+}
+
+/// Name of the group generated by the symmetries, e.g. "D4" or "C1".
+pub fn symmetry_group_name(symmetries: &[Isometry]) -> String {
+    let reflections = symmetries.iter().filter(|s| s.is_reflection()).count();
+    let rotations = symmetries.len() - reflections + 1;
+    if reflections > 0 {
+        format!("D{rotations}")
+    } else {
+        format!("C{rotations}")
+    }
+}
+
+/// 1 when two strokes share at least one point, the diagonal included.
+pub fn intersection_matrix(strokes: &[Stroke]) -> Vec<Vec<u8>> {
+    strokes
+        .iter()
+        .map(|a| {
+            strokes
+                .iter()
+                .map(|b| !stroke_intersections(a, b).is_empty() as u8)
+                .collect()
+        })
+        .collect()
+}
+
+/// Index of the image of each stroke under each symmetry, or `None` if an image is not
+/// among the strokes.
+pub fn transformation_matrix(
+    strokes: &[Stroke],
+    symmetries: &[Isometry],
+) -> Option<Vec<Vec<usize>>> {
+    strokes
+        .iter()
+        .map(|stroke| {
+            symmetries
+                .iter()
+                .map(|symmetry| find_stroke(strokes, &symmetry.apply_stroke(stroke)))
+                .collect()
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    /// Fewer than two distinct anchors, so no stroke.
+    TooFewAnchors(usize),
+    /// More parent strokes than glyph identifiers can hold.
+    TooManyStrokes(usize),
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LayoutError::TooFewAnchors(n) => {
+                write!(f, "{n} distinct anchors, at least 2 are needed")
+            }
+            LayoutError::TooManyStrokes(n) => write!(
+                f,
+                "{n} parent strokes, at most {MAX_PARENT_STROKES} are supported"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+impl Parameters {
+    /// Parameters whose parent strokes join every pair of distinct anchors, in anchor order.
+    pub fn from_anchors(anchors: &[Point]) -> Result<Self, LayoutError> {
+        let mut unique: Vec<Point> = Vec::new();
+        for anchor in anchors {
+            if find_point(&unique, anchor).is_none() {
+                unique.push(*anchor);
+            }
+        }
+        let n = unique.len();
+        if n < 2 {
+            return Err(LayoutError::TooFewAnchors(n));
+        }
+        if n * (n - 1) / 2 > MAX_PARENT_STROKES {
+            return Err(LayoutError::TooManyStrokes(n * (n - 1) / 2));
+        }
+        let parent_strokes: Vec<Stroke> = unique
+            .iter()
+            .enumerate()
+            .flat_map(|(i, a)| {
+                unique[i + 1..]
+                    .iter()
+                    .map(move |b| Stroke::line(a.x, a.y, b.x, b.y))
+            })
+            .collect();
+        let symmetries = detect_symmetries(&unique);
+        let transformation_matrix = transformation_matrix(&parent_strokes, &symmetries)
+            .expect("Symmetries of the anchors should map strokes onto strokes");
+        Ok(Parameters {
+            version: PARAMETERS_VERSION,
+            intersection_matrix: intersection_matrix(&parent_strokes),
+            parent_strokes,
+            transformation_matrix,
+        })
+    }
+
+    pub fn from_layout(layout: &Layout) -> Result<Self, LayoutError> {
+        Parameters::from_anchors(&layout.anchors())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::GlyphGenerator;
+    use crate::tests::load_parameters;
+
+    fn group(layout: Layout) -> String {
+        symmetry_group_name(&detect_symmetries(&layout.anchors()))
+    }
+
+    #[test]
+    fn test_layout_anchor_counts() {
+        assert_eq!(Layout::Square { size: 3 }.anchors().len(), 9);
+        assert_eq!(Layout::Triangular { rows: 3 }.anchors().len(), 6);
+        assert_eq!(Layout::Hexagonal { radius: 1 }.anchors().len(), 7);
+        assert_eq!(
+            Layout::Circular {
+                count: 5,
+                centre: true
+            }
+            .anchors()
+            .len(),
+            6
+        );
+    }
+
+    #[test]
+    fn test_detected_symmetry() {
+        assert_eq!(group(Layout::Square { size: 2 }), "D4");
+        assert_eq!(group(Layout::Square { size: 3 }), "D4");
+        assert_eq!(group(Layout::Triangular { rows: 3 }), "D3");
+        assert_eq!(group(Layout::Hexagonal { radius: 2 }), "D6");
+        assert_eq!(
+            group(Layout::Circular {
+                count: 5,
+                centre: false
+            }),
+            "D5"
+        );
+        assert_eq!(
+            group(Layout::Custom {
+                anchors: vec![[0.0, 0.0], [2.0, 0.0], [0.0, 1.0]]
+            }),
+            "C1"
+        );
+        assert_eq!(
+            group(Layout::Custom {
+                anchors: vec![[-1.0, 0.0], [1.0, 0.0], [0.0, 1.0]]
+            }),
+            "D1"
+        );
+    }
+
+    #[test]
+    fn test_parameters_from_layout() {
+        let parameters = Parameters::from_layout(&Layout::Triangular { rows: 2 }).unwrap();
+        assert_eq!(parameters.parent_strokes.len(), 3);
+        assert_eq!(parameters.intersection_matrix, vec![vec![1; 3]; 3]);
+        assert_eq!(parameters.transformation_matrix.len(), 3);
+        assert!(parameters
+            .transformation_matrix
+            .iter()
+            .all(|row| row.len() == 5));
+    }
+
+    #[test]
+    fn test_parameters_limits() {
+        assert_eq!(
+            Parameters::from_layout(&Layout::Square { size: 4 }),
+            Err(LayoutError::TooManyStrokes(120))
+        );
+        assert_eq!(
+            Parameters::from_layout(&Layout::Hexagonal { radius: 2 }),
+            Err(LayoutError::TooManyStrokes(171))
+        );
+        assert_eq!(
+            Parameters::from_layout(&Layout::Circular {
+                count: 12,
+                centre: true
+            }),
+            Err(LayoutError::TooManyStrokes(78))
+        );
+        assert_eq!(
+            Parameters::from_layout(&Layout::Custom {
+                anchors: vec![[0.0, 0.0], [0.0, 0.0]]
+            }),
+            Err(LayoutError::TooFewAnchors(1))
+        );
+        assert_eq!(
+            Parameters::from_layout(&Layout::Triangular { rows: 5 }),
+            Err(LayoutError::TooManyStrokes(105))
+        );
+        for layout in [
+            Layout::Square { size: 3 },
+            Layout::Triangular { rows: 4 },
+            Layout::Hexagonal { radius: 1 },
+            Layout::Circular {
+                count: 11,
+                centre: false,
+            },
+            Layout::Circular {
+                count: 10,
+                centre: true,
+            },
+        ] {
+            assert!(Parameters::from_layout(&layout).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_custom_layout_normalized() {
+        let anchors = Layout::Custom {
+            anchors: vec![[10.0, 10.0], [30.0, 10.0], [20.0, 30.0]],
+        }
+        .anchors();
+        let extent = anchors
+            .iter()
+            .map(|p| p.x.abs().max(p.y.abs()))
+            .fold(0.0, f64::max);
+        assert!((extent - 1.0).abs() < EPSILON);
+        let centre = centroid(&anchors);
+        assert!(centre.x.abs() < EPSILON && centre.y.abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_parameters_from_anchors_match_shipped() {
+        let shipped = load_parameters("parameters_9ap.json");
+        let anchors: Vec<Point> = [
+            (-1.0, -1.0),
+            (-1.0, 1.0),
+            (1.0, -1.0),
+            (1.0, 1.0),
+            (0.0, 0.0),
+            (-1.0, 0.0),
+            (1.0, 0.0),
+            (0.0, 1.0),
+            (0.0, -1.0),
+        ]
+        .iter()
+        .map(|&(x, y)| Point::new(x, y))
+        .collect();
+        let parameters = Parameters::from_anchors(&anchors).unwrap();
+
+        assert_eq!(parameters.parent_strokes, shipped.parent_strokes);
+        assert_eq!(parameters.intersection_matrix, shipped.intersection_matrix);
+
+        // Same symmetries, possibly listed in another order
+        let columns = |matrix: &Vec<Vec<usize>>| -> Vec<Vec<usize>> {
+            let mut columns: Vec<Vec<usize>> = (0..matrix[0].len())
+                .map(|j| matrix.iter().map(|row| row[j]).collect())
+                .collect();
+            columns.sort();
+            columns
+        };
+        assert_eq!(
+            columns(&parameters.transformation_matrix),
+            columns(&shipped.transformation_matrix)
+        );
+    }
+
+    #[test]
+    fn test_generate_from_layouts() {
+        let generator =
+            GlyphGenerator::new(Parameters::from_layout(&Layout::Triangular { rows: 2 }).unwrap());
+        let strokes = generator.parameters.parent_strokes.clone();
+        assert_eq!(generator.generate(&strokes, &strokes[0]).len(), 3);
+
+        let generator =
+            GlyphGenerator::new(Parameters::from_layout(&Layout::Hexagonal { radius: 1 }).unwrap());
+        let strokes = generator.parameters.parent_strokes.clone();
+        let glyphs = generator.generate(&strokes[..4], &strokes[0]);
+        assert!(!glyphs.is_empty());
+        assert_eq!(generator.parameters.transformation_matrix[0].len(), 11);
+    }
+}
@@ -1,16 +1,45 @@
-mod generate;
-mod glyph;
+pub mod alphabet;
+pub mod binary;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod checkpoint;
+pub mod complexity;
+pub mod constraints;
+pub mod contact;
+pub mod distance;
+pub mod generate;
+pub mod geometry;
+pub mod glyph;
+pub mod inspect;
 mod intersect;
-mod parameters;
-mod stroke;
+pub mod lattice;
+pub mod layout;
+pub mod normalize;
+pub mod parameters;
+pub mod progress;
+#[cfg(feature = "python")]
+mod python;
+pub mod query;
+pub mod render;
+pub mod schema;
+pub mod stepper;
+pub mod stroke;
 
 #[cfg(test)]
 mod tests;
 
+use binary::GlyphArchive;
+use constraints::Constraints;
 use generate::GlyphGenerator;
+use glyph::Glyph;
+use layout::Layout;
+use normalize::Normalization;
 use parameters::Parameters;
+use progress::{CancellationToken, Progress, ProgressObserver};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use stroke::Stroke;
+use stepper::Stepper;
+use stroke::{Stroke, StrokeRef};
 use wasm_bindgen::prelude::*;
 
 use once_cell::sync::OnceCell;
@@ -19,28 +48,314 @@ static GENERATOR: OnceCell<GlyphGenerator> = OnceCell::new();
 
 #[wasm_bindgen]
 pub fn initialize(parameters_json: String) {
-    let parameters: Parameters =
-        serde_json::from_str(&parameters_json).expect("Error deserializing parameters JSON");
+    let parameters =
+        Parameters::from_json(&parameters_json).expect("Error deserializing parameters JSON");
     let generator = GlyphGenerator::new(parameters);
     GENERATOR.set(generator).ok();
 }
 
-#[derive(Serialize, Deserialize)]
+#[wasm_bindgen]
+pub fn initialize_layout(layout_json: String) -> Result<(), JsValue> {
+    let layout: Layout = serde_json::from_str(&layout_json).map_err(js_error)?;
+    let parameters = Parameters::from_layout(&layout).map_err(js_error)?;
+    let generator = GlyphGenerator::new(parameters);
+    GENERATOR.set(generator).ok();
+    Ok(())
+}
+
+/// Generation request, the first stroke being the seed.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(test, derive(ts_rs::TS))]
 pub struct Computable {
-    strokes: Vec<Stroke>,
+    /// Format version, see `schema::COMPUTABLE_VERSION`.
+    #[serde(default = "schema::computable_version")]
+    #[cfg_attr(test, ts(as = "Option<u32>", optional))]
+    version: u32,
+    strokes: Vec<StrokeRef>,
+    #[serde(default)]
+    #[cfg_attr(test, ts(as = "Option<Constraints>", optional))]
+    constraints: Constraints,
+    #[serde(default)]
+    #[cfg_attr(test, ts(as = "Option<Normalization>", optional))]
+    normalization: Normalization,
+}
+
+fn js_error(error: impl ToString) -> JsValue {
+    JsValue::from_str(&error.to_string())
+}
+
+/// The generator the parameters were loaded into.
+fn generator() -> Result<&'static GlyphGenerator, JsValue> {
+    GENERATOR
+        .get()
+        .ok_or_else(|| js_error("Parameters JSON data not loaded"))
+}
+
+fn parse_computable(computable_json: &str) -> Result<Computable, JsValue> {
+    Computable::from_json(computable_json).map_err(js_error)
+}
+
+impl Computable {
+    /// The requested strokes, the seed first.
+    fn resolve(&self, generator: &GlyphGenerator) -> Result<Vec<Stroke>, String> {
+        let strokes: Vec<Stroke> = self
+            .strokes
+            .iter()
+            .map(|stroke| generator.resolve(stroke))
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        if strokes.is_empty() {
+            return Err("No strokes to generate from".to_string());
+        }
+        Ok(strokes)
+    }
+
+    /// Glyphs of the request, constrained then normalized, as `compute` returns them.
+    fn generate(&self, generator: &GlyphGenerator) -> Result<Vec<Glyph>, String> {
+        let strokes = self.resolve(generator)?;
+        let glyphs = generator
+            .generate_constrained(&strokes, &strokes[0], &self.constraints)
+            .map_err(|e| e.to_string())?;
+        Ok(generator.normalize(&glyphs, self.normalization))
+    }
 }
 
 #[wasm_bindgen]
 pub fn compute(computable_json: String) -> Result<String, JsValue> {
-    if GENERATOR.get().is_none() {
-        // Returning an error if the JSON data is not loaded
-        return Err(JsValue::from_str("Paremters JSON data not loaded"));
-    }
-    let generator = GENERATOR.get().unwrap();
-    let computable: Computable =
-        serde_json::from_str(&computable_json).expect("Error deserializing computable JSON");
-    let strokes = computable.strokes;
+    let generator = generator()?;
+    let result = parse_computable(&computable_json)?
+        .generate(generator)
+        .map_err(js_error)?;
+    Ok(serde_json::to_string(&result).unwrap())
+}
+
+/// Same glyphs as `compute`, each with its normalized drawing and the glyphs collapsed into it.
+#[wasm_bindgen]
+pub fn compute_normalized(computable_json: String) -> Result<String, JsValue> {
+    let generator = generator()?;
+    let computable = parse_computable(&computable_json)?;
+    let strokes = computable.resolve(generator).map_err(js_error)?;
     let seed = strokes[0];
-    let result = generator.generate(&strokes, &seed);
+    let glyphs = generator
+        .generate_constrained(&strokes, &seed, &computable.constraints)
+        .map_err(js_error)?;
+    let result = generator.normalize_report(&glyphs, computable.normalization);
     Ok(serde_json::to_string(&result).unwrap())
 }
+
+struct JsObserver<'a> {
+    callback: &'a js_sys::Function,
+    token: CancellationToken,
+}
+
+impl ProgressObserver for JsObserver<'_> {
+    fn progress(&mut self, progress: &Progress) {
+        let progress = JsValue::from_str(&serde_json::to_string(progress).unwrap());
+        if let Ok(result) = self.callback.call1(&JsValue::NULL, &progress) {
+            if result == JsValue::FALSE {
+                self.token.cancel();
+            }
+        }
+    }
+}
+
+/// Same as `compute`, calling `callback` with the progress JSON as each level starts and
+/// every hundredth of it. The generation stops with an error when the callback returns `false`.
+#[wasm_bindgen]
+pub fn compute_with_progress(
+    computable_json: String,
+    callback: &js_sys::Function,
+) -> Result<String, JsValue> {
+    let generator = generator()?;
+    let computable = parse_computable(&computable_json)?;
+    let strokes = computable.resolve(generator).map_err(js_error)?;
+    let seed = strokes[0];
+    let masks = generator
+        .constraint_masks(&computable.constraints)
+        .map_err(js_error)?;
+    let token = CancellationToken::new();
+    let mut observer = JsObserver {
+        callback,
+        token: token.clone(),
+    };
+    let result = generator
+        .generate_observed_constrained(&strokes, &seed, &masks, &mut observer, &token)
+        .map_err(js_error)?;
+    let result = generator.normalize(&result, computable.normalization);
+    Ok(serde_json::to_string(&result).unwrap())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> f64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs_f64()
+        * 1000.0
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Step {
+    glyphs: Vec<Glyph>,
+    done: bool,
+}
+
+/// `compute` run in time slices, so the page stays responsive.
+#[wasm_bindgen]
+pub struct GlyphStepper {
+    stepper: Stepper,
+}
+
+#[wasm_bindgen]
+impl GlyphStepper {
+    #[wasm_bindgen(constructor)]
+    pub fn new(computable_json: String) -> Result<GlyphStepper, JsValue> {
+        let generator = generator()?;
+        let computable = parse_computable(&computable_json)?;
+        let strokes = computable.resolve(generator).map_err(js_error)?;
+        let seed = strokes[0];
+        let masks = generator
+            .constraint_masks(&computable.constraints)
+            .map_err(js_error)?;
+        Ok(GlyphStepper {
+            stepper: generator.stepper_constrained(
+                &strokes,
+                &seed,
+                &masks,
+                computable.normalization,
+            ),
+        })
+    }
+
+    /// Works for about `budget_ms` milliseconds, returning the glyphs completed meanwhile
+    /// and whether the generation is over, as JSON.
+    pub fn step(&mut self, budget_ms: f64) -> Result<String, JsValue> {
+        let generator = generator()?;
+        let deadline = now_ms() + budget_ms;
+        let glyphs = self.stepper.step(generator, || now_ms() >= deadline);
+        let step = Step {
+            glyphs,
+            done: self.stepper.is_done(),
+        };
+        Ok(serde_json::to_string(&step).unwrap())
+    }
+
+    pub fn done(&self) -> bool {
+        self.stepper.is_done()
+    }
+}
+
+/// Same glyphs as `compute`, encoded as a glyph archive.
+#[wasm_bindgen]
+pub fn compute_binary(computable_json: String, compress: bool) -> Result<Vec<u8>, JsValue> {
+    let generator = generator()?;
+    let glyphs = parse_computable(&computable_json)?
+        .generate(generator)
+        .map_err(js_error)?;
+    Ok(generator.archive(&glyphs).to_bytes(compress))
+}
+
+/// Glyphs JSON of a glyph archive.
+#[wasm_bindgen]
+pub fn decode_binary(bytes: &[u8]) -> Result<String, JsValue> {
+    let archive = GlyphArchive::from_bytes(bytes).map_err(js_error)?;
+    Ok(serde_json::to_string(&archive.glyphs()).unwrap())
+}
+
+/// SVG debug drawing of the parameters, highlighting the links of the selected stroke.
+#[wasm_bindgen]
+pub fn render_parameters(selected: Option<usize>, size: f64) -> Result<String, JsValue> {
+    let generator = generator()?;
+    if selected.is_some_and(|i| i >= generator.parameters.parent_strokes.len()) {
+        return Err(JsValue::from_str("Selected stroke out of range"));
+    }
+    Ok(generator.render_parameters(selected, size))
+}
+
+/// Lattice JSON of the generation `compute` runs, which must not ask for a normalization.
+#[wasm_bindgen]
+pub fn compute_lattice(computable_json: String) -> Result<String, JsValue> {
+    let generator = generator()?;
+    let computable = parse_computable(&computable_json)?;
+    if computable.normalization != Normalization::None {
+        return Err(JsValue::from_str("Lattices do not support normalization"));
+    }
+    let strokes = computable.resolve(generator).map_err(js_error)?;
+    let seed = strokes[0];
+    let lattice = generator
+        .generate_lattice_constrained(&strokes, &seed, &computable.constraints)
+        .map_err(js_error)?;
+    Ok(lattice.to_json())
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Extendable {
+    glyphs: Vec<Vec<StrokeRef>>,
+    #[serde(default)]
+    palette: Vec<StrokeRef>,
+    extra_strokes: usize,
+}
+
+#[wasm_bindgen]
+pub fn extend(extendable_json: String) -> Result<String, JsValue> {
+    let generator = generator()?;
+    let extendable: Extendable = serde_json::from_str(&extendable_json).map_err(js_error)?;
+    let resolve = |strokes: &[StrokeRef]| -> Result<Vec<Stroke>, JsValue> {
+        strokes
+            .iter()
+            .map(|s| generator.resolve(s))
+            .collect::<Result<_, _>>()
+            .map_err(js_error)
+    };
+    let starts: Vec<Glyph> = extendable
+        .glyphs
+        .iter()
+        .map(|strokes| {
+            Ok(Glyph {
+                strokes: resolve(strokes)?,
+            })
+        })
+        .collect::<Result<_, JsValue>>()?;
+    let palette = resolve(&extendable.palette)?;
+    let result = generator.extend(&starts, &palette, extendable.extra_strokes);
+    Ok(serde_json::to_string(&result).unwrap())
+}
+
+/// Generated from the Rust types, see the schema tests.
+#[wasm_bindgen(typescript_custom_section)]
+const TYPESCRIPT_TYPES: &'static str = include_str!("../schemas/types.d.ts");
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Parameters")]
+    pub type JsParameters;
+    #[wasm_bindgen(typescript_type = "Computable")]
+    pub type JsComputable;
+    #[wasm_bindgen(typescript_type = "Glyph[]")]
+    pub type JsGlyphs;
+}
+
+/// Same as `initialize`, taking the parameters as an object.
+#[wasm_bindgen]
+pub fn initialize_parameters(parameters: JsParameters) -> Result<(), JsValue> {
+    let value: serde_json::Value = serde_wasm_bindgen::from_value(parameters.into())?;
+    let parameters = Parameters::from_value(value).map_err(js_error)?;
+    GENERATOR.set(GlyphGenerator::new(parameters)).ok();
+    Ok(())
+}
+
+/// Same as `compute`, taking and returning objects.
+#[wasm_bindgen]
+pub fn compute_glyphs(computable: JsComputable) -> Result<JsGlyphs, JsValue> {
+    let generator = generator()?;
+    let value: serde_json::Value = serde_wasm_bindgen::from_value(computable.into())?;
+    let computable = Computable::from_value(value).map_err(js_error)?;
+    let result = computable.generate(generator).map_err(js_error)?;
+    Ok(serde_wasm_bindgen::to_value(&result)?.unchecked_into())
+}
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+
+use crate::generate::GlyphGenerator;
+use crate::geometry::{end, start, stroke_intersections, strokes_overlap, EPSILON};
+use crate::glyph::Glyph;
+use crate::stroke::{Stroke, StrokeKey};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// When two glyphs count as the same glyph.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum Normalization {
+    /// Glyphs with different stroke sets are different.
+    #[default]
+    None,
+    /// Glyphs drawing the same strokes once collinear overlapping strokes are merged are the same.
+    Overlaps,
+    /// Glyphs with the same maximal segments, that is drawing the same figure, are the same.
+    Planar,
+}
+
+/// A glyph kept by a normalization, with the glyphs dropped as drawing the same figure.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+pub struct NormalizedGlyph {
+    pub glyph: Glyph,
+    /// Strokes the glyph draws under the normalization.
+    pub drawing: Vec<Stroke>,
+    pub collapsed: Vec<Glyph>,
+}
+
+/// Whether the ends of `b` lie on the line of `a`, within a tolerance relative to the longer
+/// stroke so that scaling a drawing does not change the answer.
+fn is_collinear(a: &Stroke, b: &Stroke) -> bool {
+    let (dx, dy) = (a.x1 - a.x0, a.y1 - a.y0);
+    let length = dx.hypot(dy);
+    // The cross product is the distance to the line times the length of `a`
+    let tolerance = EPSILON * length * length.max(start(b).distance(&end(b)));
+    [start(b), end(b)]
+        .iter()
+        .all(|p| (dx * (p.y - a.y0) - dy * (p.x - a.x0)).abs() <= tolerance)
+}
+
+/// Smallest straight stroke covering two collinear straight strokes, in the direction of `a`.
+fn merge(a: &Stroke, b: &Stroke) -> Stroke {
+    let (dx, dy) = (a.x1 - a.x0, a.y1 - a.y0);
+    let points = [start(a), end(a), start(b), end(b)];
+    let along = |i: &usize| (points[*i].x - a.x0) * dx + (points[*i].y - a.y0) * dy;
+    let first = (0..4).min_by(|i, j| along(i).total_cmp(&along(j))).unwrap();
+    let last = (0..4).max_by(|i, j| along(i).total_cmp(&along(j))).unwrap();
+    let (p0, p1) = (points[first], points[last]);
+    Stroke::line(p0.x, p0.y, p1.x, p1.y)
+}
+
+/// Strokes with the straight strokes merged with the collinear straight strokes `joins`
+/// says they join, each merged stroke taking the place of the first of its strokes.
+fn merge_collinear(strokes: &[Stroke], joins: impl Fn(&Stroke, &Stroke) -> bool) -> Vec<Stroke> {
+    let mut strokes = strokes.to_vec();
+    'merging: loop {
+        for i in 0..strokes.len() {
+            for j in i + 1..strokes.len() {
+                let (a, b) = (&strokes[i], &strokes[j]);
+                if a.kind.is_line() && b.kind.is_line() && is_collinear(a, b) && joins(a, b) {
+                    strokes[i] = merge(a, b);
+                    strokes.remove(j);
+                    continue 'merging;
+                }
+            }
+        }
+        return strokes;
+    }
+}
+
+/// Strokes with the straight strokes sharing a piece of positive length merged.
+pub fn merge_overlaps(strokes: &[Stroke]) -> Vec<Stroke> {
+    merge_collinear(strokes, strokes_overlap)
+}
+
+/// Strokes with the collinear straight strokes sharing a point merged, leaving the maximal
+/// straight segments of the drawing.
+pub fn maximal_segments(strokes: &[Stroke]) -> Vec<Stroke> {
+    merge_collinear(strokes, |a, b| !stroke_intersections(a, b).is_empty())
+}
+
+impl GlyphGenerator {
+    /// Strokes the glyph draws under the normalization.
+    pub fn drawing(&self, glyph: &Glyph, normalization: Normalization) -> Vec<Stroke> {
+        match normalization {
+            Normalization::None => glyph.strokes.clone(),
+            Normalization::Overlaps => merge_overlaps(&glyph.strokes),
+            Normalization::Planar => maximal_segments(&glyph.strokes),
+        }
+    }
+
+    /// Key shared by the glyphs with the same drawing up to symmetry.
+    pub(crate) fn drawing_key(
+        &self,
+        glyph: &Glyph,
+        normalization: Normalization,
+    ) -> Vec<StrokeKey> {
+        let internal = self.from_glyph(glyph);
+        std::iter::once(internal.clone())
+            .chain(self.transform(&internal))
+            .map(|image| {
+                let mut keys: Vec<StrokeKey> = self
+                    .drawing(&self.to_glyph(&image), normalization)
+                    .iter()
+                    .map(|stroke| stroke.key(self.quantum))
+                    .collect();
+                keys.sort();
+                keys
+            })
+            .min()
+            .unwrap()
+    }
+
+    /// The glyphs without those drawing, under the normalization and up to symmetry, the
+    /// same as an earlier glyph, each with the glyphs dropped in its favour. Generated glyphs
+    /// come by increasing number of strokes, so each drawing keeps its glyph with the fewest
+    /// strokes.
+    pub fn normalize_report(
+        &self,
+        glyphs: &[Glyph],
+        normalization: Normalization,
+    ) -> Vec<NormalizedGlyph> {
+        if normalization == Normalization::None {
+            return glyphs
+                .iter()
+                .map(|glyph| NormalizedGlyph {
+                    glyph: glyph.clone(),
+                    drawing: glyph.strokes.clone(),
+                    collapsed: Vec::new(),
+                })
+                .collect();
+        }
+        let mut kept: HashMap<Vec<StrokeKey>, usize> = HashMap::new();
+        let mut normalized: Vec<NormalizedGlyph> = Vec::new();
+        for glyph in glyphs {
+            let key = self.drawing_key(glyph, normalization);
+            match kept.get(&key) {
+                Some(&i) => normalized[i].collapsed.push(glyph.clone()),
+                None => {
+                    kept.insert(key, normalized.len());
+                    normalized.push(NormalizedGlyph {
+                        glyph: glyph.clone(),
+                        drawing: self.drawing(glyph, normalization),
+                        collapsed: Vec::new(),
+                    });
+                }
+            }
+        }
+        normalized
+    }
+
+    /// The glyphs `normalize_report` keeps.
+    pub fn normalize(&self, glyphs: &[Glyph], normalization: Normalization) -> Vec<Glyph> {
+        self.normalize_report(glyphs, normalization)
+            .into_iter()
+            .map(|normalized| normalized.glyph)
+            .collect()
+    }
+
+    /// Same as `generate`, normalized.
+    pub fn generate_normalized(
+        &self,
+        strokes: &[Stroke],
+        seed: &Stroke,
+        normalization: Normalization,
+    ) -> Vec<NormalizedGlyph> {
+        self.normalize_report(&self.generate(strokes, seed), normalization)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::setup;
+
+    #[test]
+    fn test_merge_overlaps() {
+        let diagonal = Stroke::line(-1.0, -1.0, 1.0, 1.0);
+        let half = Stroke::line(0.0, 0.0, -1.0, -1.0);
+        let vertical = Stroke::line(-1.0, -1.0, -1.0, 1.0);
+        assert_eq!(
+            merge_overlaps(&[half, vertical, diagonal]),
+            vec![diagonal, vertical]
+        );
+
+        // Partial overlap extends the stroke, end to end contact does not merge
+        let shifted = Stroke::line(0.0, 0.0, 2.0, 2.0);
+        assert_eq!(
+            merge_overlaps(&[diagonal, shifted]),
+            vec![Stroke::line(-1.0, -1.0, 2.0, 2.0)]
+        );
+        let other_half = Stroke::line(0.0, 0.0, 1.0, 1.0);
+        assert_eq!(merge_overlaps(&[half, other_half]), vec![half, other_half]);
+    }
+
+    #[test]
+    fn test_maximal_segments() {
+        let half = Stroke::line(-1.0, -1.0, 0.0, 0.0);
+        let other_half = Stroke::line(1.0, 1.0, 0.0, 0.0);
+        let vertical = Stroke::line(0.0, 0.0, 0.0, 1.0);
+        assert_eq!(
+            maximal_segments(&[half, vertical, other_half]),
+            vec![Stroke::line(-1.0, -1.0, 1.0, 1.0), vertical]
+        );
+        assert_eq!(maximal_segments(&[half, vertical]), vec![half, vertical]);
+    }
+
+    #[test]
+    fn test_collinear_at_any_scale() {
+        for scale in [1e-6, 1.0, 1e7] {
+            let line = |x0: f64, y0: f64, x1: f64, y1: f64| {
+                Stroke::line(x0 * scale, y0 * scale, x1 * scale, y1 * scale)
+            };
+            let a = line(0.1, 0.3, 0.7, 2.1);
+            assert!(is_collinear(&a, &line(0.3, 0.9, 0.5, 1.5)));
+            assert!(!is_collinear(&a, &line(0.3, 0.9, 0.5, 1.6)));
+            assert!(!is_collinear(&a, &line(0.1, 0.3, 0.7, 0.3)));
+        }
+    }
+
+    #[test]
+    fn test_normalize_overlaps() {
+        let context = setup();
+        let generator = context.advanced;
+        let diagonal = Stroke::line(-1.0, -1.0, 1.0, 1.0);
+        let half = Stroke::line(-1.0, -1.0, 0.0, 0.0);
+        let vertical = Stroke::line(-1.0, -1.0, -1.0, 1.0);
+        let strokes = [diagonal, half, vertical];
+
+        let glyphs = generator.generate(&strokes, &diagonal);
+        assert_eq!(glyphs.len(), 4);
+        assert_eq!(generator.normalize(&glyphs, Normalization::None), glyphs);
+        let normalized = generator.normalize(&glyphs, Normalization::Overlaps);
+        assert_eq!(normalized.len(), 2);
+        for glyph in &normalized {
+            assert!(!glyph.strokes.contains(&half));
+        }
+
+        // The same drawing up to symmetry
+        let mirrored = Glyph {
+            strokes: vec![diagonal, Stroke::line(1.0, 1.0, 0.0, 0.0)],
+        };
+        assert_eq!(
+            generator
+                .normalize(&[glyphs[0].clone(), mirrored], Normalization::Overlaps)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_normalize_planar() {
+        let context = setup();
+        let generator = context.advanced;
+        let glyph = |strokes: &[Stroke]| Glyph {
+            strokes: strokes.to_vec(),
+        };
+        let diagonal = Stroke::line(-1.0, -1.0, 1.0, 1.0);
+        let half = Stroke::line(-1.0, -1.0, 0.0, 0.0);
+        let other_half = Stroke::line(0.0, 0.0, 1.0, 1.0);
+        let glyphs = [
+            glyph(&[diagonal]),
+            glyph(&[half]),
+            glyph(&[half, other_half]),
+            glyph(&[diagonal, half]),
+        ];
+
+        let overlaps = generator.normalize_report(&glyphs, Normalization::Overlaps);
+        assert_eq!(overlaps.len(), 3);
+        assert_eq!(overlaps[0].collapsed, vec![glyphs[3].clone()]);
+
+        let planar = generator.normalize_report(&glyphs, Normalization::Planar);
+        assert_eq!(planar.len(), 2);
+        assert_eq!(planar[0].glyph, glyphs[0]);
+        assert_eq!(planar[0].drawing, vec![diagonal]);
+        assert_eq!(
+            planar[0].collapsed,
+            vec![glyphs[2].clone(), glyphs[3].clone()]
+        );
+        assert!(planar[1].collapsed.is_empty());
+
+        let strokes = generator.parameters.parent_strokes[..8].to_vec();
+        let normalized =
+            generator.generate_normalized(&strokes, &strokes[0], Normalization::Planar);
+        let generated = generator.generate(&strokes, &strokes[0]);
+        assert_eq!(
+            normalized
+                .iter()
+                .map(|n| 1 + n.collapsed.len())
+                .sum::<usize>(),
+            generated.len()
+        );
+    }
+}
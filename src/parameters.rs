@@ -1,9 +1,59 @@
+use std::fmt;
+
+use crate::geometry::{end, start, Point};
+use crate::glyph::MAX_PARENT_STROKES;
+use crate::schema;
 use crate::stroke::Stroke;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(test, derive(ts_rs::TS))]
 pub struct Parameters {
+    /// Format version, see `schema::PARAMETERS_VERSION`.
+    #[serde(default = "schema::parameters_version")]
+    #[cfg_attr(test, ts(as = "Option<u32>", optional))]
+    pub version: u32,
     pub parent_strokes: Vec<Stroke>,
     pub intersection_matrix: Vec<Vec<u8>>,
     pub transformation_matrix: Vec<Vec<usize>>,
 }
+
+/// More parent strokes than glyph identifiers can hold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TooManyStrokes(pub usize);
+
+impl fmt::Display for TooManyStrokes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} parent strokes, at most {MAX_PARENT_STROKES} are supported",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for TooManyStrokes {}
+
+impl Parameters {
+    /// Checks glyph identifiers, one bit per parent stroke, can hold the parent strokes.
+    pub fn check_size(&self) -> Result<(), TooManyStrokes> {
+        match self.parent_strokes.len() {
+            n if n > MAX_PARENT_STROKES => Err(TooManyStrokes(n)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Distinct stroke endpoints, in order of first appearance in the parent strokes.
+    pub fn anchors(&self) -> Vec<Point> {
+        let mut anchors: Vec<Point> = Vec::new();
+        for stroke in &self.parent_strokes {
+            for point in [start(stroke), end(stroke)] {
+                if anchors.iter().all(|p| p.key() != point.key()) {
+                    anchors.push(point);
+                }
+            }
+        }
+        anchors
+    }
+}
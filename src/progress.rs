@@ -0,0 +1,297 @@
+use std::fmt;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+use crate::constraints::ConstraintMasks;
+use crate::generate::{Generation, GlyphGenerator};
+use crate::glyph::{Glyph, InternalGlyph};
+use crate::stroke::Stroke;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    /// Level being expanded, the seed being level 0.
+    pub level: usize,
+    pub levels: usize,
+    /// Glyphs generated so far, the frontier included.
+    pub glyphs_found: usize,
+    /// Number of glyphs of the level being expanded.
+    pub frontier_size: usize,
+    /// Number of glyphs of the level expanded so far.
+    pub expanded: usize,
+    /// Rough estimate of the glyphs still to generate once the level is expanded: every
+    /// combination of the remaining strokes, divided by the number of symmetries. Neither
+    /// a lower nor an upper bound, as it ignores connectivity, constraints and glyphs with
+    /// fewer images than there are symmetries.
+    pub estimated_remaining: f64,
+}
+
+/// Most reports made while expanding a level, besides the one starting it.
+pub const PROGRESS_REPORTS: usize = 100;
+
+/// Number of glyphs expanded between two reports on a level of `frontier_size` glyphs.
+pub fn report_interval(frontier_size: usize) -> usize {
+    frontier_size.div_ceil(PROGRESS_REPORTS).max(1)
+}
+
+/// Receives the progress of a generation, as each level starts and every
+/// `PROGRESS_REPORTS`th of it.
+pub trait ProgressObserver {
+    fn progress(&mut self, progress: &Progress);
+}
+
+impl<F: FnMut(&Progress)> ProgressObserver for F {
+    fn progress(&mut self, progress: &Progress) {
+        self(progress)
+    }
+}
+
+impl ProgressObserver for Sender<Progress> {
+    fn progress(&mut self, progress: &Progress) {
+        // The receiver going away does not stop the generation
+        self.send(progress.clone()).ok();
+    }
+}
+
+/// Shared flag stopping a generation, checked before expanding each glyph.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Generation cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+impl GlyphGenerator {
+    /// Estimate reported in `Progress::estimated_remaining` once `level` is expanded.
+    pub fn estimate_remaining(&self, strokes: usize, level: usize) -> f64 {
+        let symmetries = self
+            .parameters
+            .transformation_matrix
+            .first()
+            .map_or(1, |transformations| transformations.len() + 1) as f64;
+        (level + 1..strokes)
+            .map(|k| binomial(strokes - 1, k))
+            .sum::<f64>()
+            / symmetries
+    }
+
+    /// Same glyphs as `generate`, reporting progress as a level starts and every
+    /// `PROGRESS_REPORTS`th of it, and stopping once the token is cancelled.
+    pub fn generate_observed(
+        &self,
+        strokes: &[Stroke],
+        seed: &Stroke,
+        observer: &mut dyn ProgressObserver,
+        token: &CancellationToken,
+    ) -> Result<Vec<Glyph>, Cancelled> {
+        self.generate_observed_constrained(
+            strokes,
+            seed,
+            &ConstraintMasks::default(),
+            observer,
+            token,
+        )
+    }
+
+    /// Same glyphs as `generate_constrained`, observed as in `generate_observed`.
+    pub fn generate_observed_constrained(
+        &self,
+        strokes: &[Stroke],
+        seed: &Stroke,
+        masks: &ConstraintMasks,
+        observer: &mut dyn ProgressObserver,
+        token: &CancellationToken,
+    ) -> Result<Vec<Glyph>, Cancelled> {
+        let mut glyphs: Vec<InternalGlyph> = Vec::new();
+        let mut control = |generation: &Generation, completed: bool| {
+            let (level, expansion) = (generation.level(), generation.expansion());
+            if completed {
+                glyphs.extend_from_slice(level);
+            }
+            if completed
+                || !expansion.is_done(level)
+                    && expansion.expanded() % report_interval(level.len()) == 0
+            {
+                observer.progress(&Progress {
+                    level: generation.level_index(),
+                    levels: generation.levels(),
+                    glyphs_found: glyphs.len() + expansion.found(),
+                    frontier_size: level.len(),
+                    expanded: expansion.expanded(),
+                    estimated_remaining: self
+                        .estimate_remaining(generation.levels(), generation.level_index()),
+                });
+            }
+            if token.is_cancelled() {
+                ControlFlow::Break(Cancelled)
+            } else {
+                ControlFlow::Continue(())
+            }
+        };
+
+        // The seed level counts as completed
+        let mut generation = Generation::new(self, strokes, seed, masks.clone());
+        let flow = match control(&generation, true) {
+            ControlFlow::Continue(()) => generation.run(self, |_, _, _, _| {}, &mut control),
+            cancelled => cancelled,
+        };
+        if let ControlFlow::Break(cancelled) = flow {
+            return Err(cancelled);
+        }
+
+        Ok(glyphs
+            .iter()
+            .filter_map(|g| self.admissible(g, masks))
+            .map(|g| self.to_glyph(&g))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::Constraints;
+    use crate::stroke::StrokeRef;
+    use crate::tests::setup;
+
+    #[test]
+    fn test_generate_observed() {
+        let context = setup();
+        let generator = context.basic;
+        let strokes = generator.parameters.parent_strokes.clone();
+        let expected = generator.generate(&strokes, &strokes[0]);
+
+        let mut reports: Vec<Progress> = Vec::new();
+        let mut observer = |progress: &Progress| reports.push(progress.clone());
+        let glyphs = generator
+            .generate_observed(
+                &strokes,
+                &strokes[0],
+                &mut observer,
+                &CancellationToken::new(),
+            )
+            .unwrap();
+        assert_eq!(glyphs, expected);
+        let levels =
+            generator.generate_levels(&strokes, &strokes[0], &Default::default(), |_, _, _, _| {});
+        let expected_reports: usize = levels[..strokes.len() - 1]
+            .iter()
+            .map(|level| level.len().max(1))
+            .sum();
+        assert_eq!(reports.len(), expected_reports + 1);
+        assert_eq!(reports[0].frontier_size, 1);
+        assert_eq!(reports.last().unwrap().glyphs_found, expected.len());
+        assert_eq!(reports.last().unwrap().estimated_remaining, 0.0);
+        assert!(reports
+            .windows(2)
+            .all(|w| w[0].glyphs_found <= w[1].glyphs_found));
+
+        let (mut sender, receiver) = std::sync::mpsc::channel();
+        generator
+            .generate_observed(
+                &strokes,
+                &strokes[0],
+                &mut sender,
+                &CancellationToken::new(),
+            )
+            .unwrap();
+        assert_eq!(receiver.try_iter().collect::<Vec<Progress>>(), reports);
+
+        // Cancelled halfway through
+        let token = CancellationToken::new();
+        let mut observer = |progress: &Progress| {
+            if progress.level == 2 {
+                token.cancel();
+            }
+        };
+        let result = generator.generate_observed(&strokes, &strokes[0], &mut observer, &token);
+        assert_eq!(result, Err(Cancelled));
+
+        // Cancelled in the middle of a level
+        let token = CancellationToken::new();
+        let mut last: Option<Progress> = None;
+        let mut observer = |progress: &Progress| {
+            if progress.expanded == 1 {
+                token.cancel();
+            }
+            last = Some(progress.clone());
+        };
+        let result = generator.generate_observed(&strokes, &strokes[0], &mut observer, &token);
+        assert_eq!(result, Err(Cancelled));
+        let last = last.unwrap();
+        assert!(last.expanded == 1 && last.frontier_size > 1);
+
+        let constraints = Constraints {
+            forbidden: vec![StrokeRef::Stroke(strokes[5])],
+            ..Default::default()
+        };
+        let glyphs = generator
+            .generate_observed_constrained(
+                &strokes,
+                &strokes[0],
+                &generator.constraint_masks(&constraints).unwrap(),
+                &mut |_: &Progress| {},
+                &CancellationToken::new(),
+            )
+            .unwrap();
+        assert_eq!(
+            glyphs,
+            generator
+                .generate_constrained(&strokes, &strokes[0], &constraints)
+                .unwrap()
+        );
+
+        // Levels past a hundred glyphs are reported in chunks
+        assert_eq!(report_interval(0), 1);
+        assert_eq!(report_interval(100), 1);
+        assert_eq!(report_interval(101), 2);
+        let advanced = context.advanced;
+        let strokes = advanced.parameters.parent_strokes[..12].to_vec();
+        let mut reports: Vec<Progress> = Vec::new();
+        advanced
+            .generate_observed(
+                &strokes,
+                &strokes[0],
+                &mut |progress: &Progress| reports.push(progress.clone()),
+                &CancellationToken::new(),
+            )
+            .unwrap();
+        assert!(reports.iter().any(|r| r.frontier_size > PROGRESS_REPORTS));
+        for level in 0..strokes.len() {
+            let count = reports.iter().filter(|r| r.level == level).count();
+            assert!((1..=PROGRESS_REPORTS).contains(&count));
+        }
+
+        let mut parameters = generator.parameters.clone();
+        parameters.transformation_matrix.clear();
+        let asymmetric = GlyphGenerator::new(parameters);
+        assert_eq!(asymmetric.estimate_remaining(3, 0), 3.0);
+    }
+}
@@ -0,0 +1,279 @@
+use ndarray::Array2;
+use numpy::{IntoPyArray, PyArray1, PyArray2};
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+
+use crate::generate::GlyphGenerator;
+use crate::glyph::{Glyph, InternalGlyph};
+use crate::layout::Layout;
+use crate::parameters::Parameters;
+use crate::stroke::Stroke;
+
+fn value_error(error: impl ToString) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+/// Request the bindings cannot run, raised as a Python exception.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RequestError {
+    NoParentStroke(usize),
+    NoStrokes,
+}
+
+impl From<RequestError> for PyErr {
+    fn from(error: RequestError) -> Self {
+        match error {
+            RequestError::NoParentStroke(i) => {
+                PyIndexError::new_err(format!("No parent stroke {i}"))
+            }
+            RequestError::NoStrokes => PyValueError::new_err("No strokes to generate from"),
+        }
+    }
+}
+
+/// One `x0, y0, x1, y1` row per stroke.
+fn endpoint_rows(strokes: &[Stroke]) -> Array2<f64> {
+    Array2::from_shape_fn((strokes.len(), 4), |(i, j)| {
+        let s = &strokes[i];
+        [s.x0, s.y0, s.x1, s.y1][j]
+    })
+}
+
+/// Rows of equal length as a 2D array, as many columns as the first row.
+fn rows_array<T: Copy>(rows: &[Vec<T>]) -> Array2<T> {
+    let columns = rows.first().map_or(0, |row| row.len());
+    Array2::from_shape_fn((rows.len(), columns), |(i, j)| rows[i][j])
+}
+
+/// One row per glyph, one column per parent stroke, true where the glyph uses the stroke.
+fn membership_rows(glyphs: &[InternalGlyph], strokes: usize) -> Array2<bool> {
+    Array2::from_shape_fn((glyphs.len(), strokes), |(i, j)| {
+        glyphs[i]
+            .identifier
+            .checked_shr(j as u32)
+            .is_some_and(|bits| bits & 1 != 0)
+    })
+}
+
+#[pyclass(name = "Parameters")]
+#[derive(Clone)]
+pub struct PyParameters {
+    inner: Parameters,
+}
+
+#[pymethods]
+impl PyParameters {
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let inner = Parameters::from_json(json).map_err(value_error)?;
+        Ok(PyParameters { inner })
+    }
+
+    #[staticmethod]
+    fn from_layout(layout_json: &str) -> PyResult<Self> {
+        let layout: Layout = serde_json::from_str(layout_json).map_err(value_error)?;
+        let inner = Parameters::from_layout(&layout).map_err(value_error)?;
+        Ok(PyParameters { inner })
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string(&self.inner).unwrap()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.parent_strokes.len()
+    }
+
+    /// Endpoints of the parent strokes, one `x0, y0, x1, y1` row per stroke.
+    #[getter]
+    fn parent_strokes<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        endpoint_rows(&self.inner.parent_strokes).into_pyarray(py)
+    }
+
+    #[getter]
+    fn intersection_matrix<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<u8>> {
+        rows_array(&self.inner.intersection_matrix).into_pyarray(py)
+    }
+
+    #[getter]
+    fn transformation_matrix<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<usize>> {
+        rows_array(&self.inner.transformation_matrix).into_pyarray(py)
+    }
+}
+
+/// Glyphs are given and returned as lists of parent stroke indices.
+#[pyclass(name = "GlyphGenerator")]
+pub struct PyGlyphGenerator {
+    inner: GlyphGenerator,
+}
+
+impl PyGlyphGenerator {
+    fn strokes(&self, indices: &[usize]) -> Result<Vec<Stroke>, RequestError> {
+        let parent_strokes = &self.inner.parameters.parent_strokes;
+        indices
+            .iter()
+            .map(|&i| {
+                parent_strokes
+                    .get(i)
+                    .copied()
+                    .ok_or(RequestError::NoParentStroke(i))
+            })
+            .collect()
+    }
+
+    /// All parent strokes when `strokes` is not given, seeded with the first of them.
+    fn run(&self, strokes: Option<Vec<usize>>) -> Result<Vec<Vec<InternalGlyph>>, RequestError> {
+        let indices =
+            strokes.unwrap_or_else(|| (0..self.inner.parameters.parent_strokes.len()).collect());
+        let strokes = self.strokes(&indices)?;
+        let Some(seed) = strokes.first() else {
+            return Err(RequestError::NoStrokes);
+        };
+        Ok(self
+            .inner
+            .generate_levels(&strokes, seed, &Default::default(), |_, _, _, _| {}))
+    }
+}
+
+#[pymethods]
+impl PyGlyphGenerator {
+    #[new]
+    fn new(parameters: &PyParameters) -> Self {
+        PyGlyphGenerator {
+            inner: GlyphGenerator::new(parameters.inner.clone()),
+        }
+    }
+
+    #[pyo3(signature = (strokes=None))]
+    fn generate(&self, strokes: Option<Vec<usize>>) -> PyResult<Vec<Vec<usize>>> {
+        Ok(self
+            .run(strokes)?
+            .iter()
+            .flatten()
+            .map(|g| g.strokes.iter().map(|s| s.index).collect())
+            .collect())
+    }
+
+    /// One row per generated glyph, one column per parent stroke, true where the glyph
+    /// uses the stroke.
+    #[pyo3(signature = (strokes=None))]
+    fn generate_matrix<'py>(
+        &self,
+        py: Python<'py>,
+        strokes: Option<Vec<usize>>,
+    ) -> PyResult<Bound<'py, PyArray2<bool>>> {
+        let glyphs: Vec<InternalGlyph> = self.run(strokes)?.into_iter().flatten().collect();
+        let n = self.inner.parameters.parent_strokes.len();
+        Ok(membership_rows(&glyphs, n).into_pyarray(py))
+    }
+
+    /// Number of glyphs of each level.
+    #[pyo3(signature = (strokes=None))]
+    fn count<'py>(
+        &self,
+        py: Python<'py>,
+        strokes: Option<Vec<usize>>,
+    ) -> PyResult<Bound<'py, PyArray1<usize>>> {
+        let counts: Vec<usize> = self.run(strokes)?.iter().map(|level| level.len()).collect();
+        Ok(counts.into_pyarray(py))
+    }
+
+    #[pyo3(signature = (glyph, size=100.0))]
+    fn render(&self, glyph: Vec<usize>, size: f64) -> PyResult<String> {
+        let glyph = Glyph {
+            strokes: self.strokes(&glyph)?,
+        };
+        Ok(self.inner.render(&glyph, size))
+    }
+
+    fn complexity(&self, glyph: Vec<usize>) -> PyResult<f64> {
+        let glyph = Glyph {
+            strokes: self.strokes(&glyph)?,
+        };
+        Ok(self.inner.complexity(&glyph).score(&Default::default()))
+    }
+}
+
+#[pymodule]
+fn glyphs_generator(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyParameters>()?;
+    module.add_class::<PyGlyphGenerator>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generator() -> PyGlyphGenerator {
+        let parameters = Parameters::from_json(include_str!("../tests/parameters_4ap.json"));
+        PyGlyphGenerator {
+            inner: GlyphGenerator::new(parameters.unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_array_conversions() {
+        let generator = generator();
+        let parameters = &generator.inner.parameters;
+        let endpoints = endpoint_rows(&parameters.parent_strokes);
+        assert_eq!(endpoints.shape(), [6, 4]);
+        let stroke = parameters.parent_strokes[1];
+        assert_eq!(
+            endpoints.row(1).to_vec(),
+            [stroke.x0, stroke.y0, stroke.x1, stroke.y1]
+        );
+
+        let intersections = rows_array(&parameters.intersection_matrix);
+        assert_eq!(intersections.shape(), [6, 6]);
+        assert_eq!(intersections[[2, 3]], parameters.intersection_matrix[2][3]);
+        let transformations = rows_array(&parameters.transformation_matrix);
+        assert_eq!(
+            transformations.shape(),
+            [6, parameters.transformation_matrix[0].len()]
+        );
+        assert_eq!(rows_array::<usize>(&[]).shape(), [0, 0]);
+
+        let glyphs = [
+            InternalGlyph::from_identifier(0b000101),
+            InternalGlyph::from_identifier(1 << 63),
+        ];
+        let membership = membership_rows(&glyphs, 6);
+        assert_eq!(
+            membership.row(0).to_vec(),
+            [true, false, true, false, false, false]
+        );
+        assert!(membership.row(1).iter().all(|&used| !used));
+        // Strokes past the identifier width belong to no glyph
+        assert!(!membership_rows(&glyphs, 70)[[1, 69]]);
+        assert!(membership_rows(&glyphs, 70)[[1, 63]]);
+    }
+
+    #[test]
+    fn test_generation() {
+        let generator = generator();
+        let strokes = generator.inner.parameters.parent_strokes.clone();
+        let levels = generator.run(None).unwrap();
+        assert_eq!(levels.len(), strokes.len());
+        let glyphs: Vec<Glyph> = levels
+            .iter()
+            .flatten()
+            .map(|g| generator.inner.to_glyph(g))
+            .collect();
+        assert_eq!(glyphs, generator.inner.generate(&strokes, &strokes[0]));
+
+        let levels = generator.run(Some(vec![2, 0, 1])).unwrap();
+        assert_eq!(levels[0][0].identifier, 0b100);
+        assert_eq!(levels.len(), 3);
+
+        assert_eq!(generator.run(Some(vec![])), Err(RequestError::NoStrokes));
+        assert_eq!(
+            generator.run(Some(vec![6])),
+            Err(RequestError::NoParentStroke(6))
+        );
+        assert_eq!(
+            generator.strokes(&[0, 7]),
+            Err(RequestError::NoParentStroke(7))
+        );
+    }
+}
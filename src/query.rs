@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use crate::generate::GlyphGenerator;
+use crate::glyph::{Glyph, InternalGlyph};
+use serde::{Deserialize, Serialize};
+
+/// An image of a motif found inside a glyph of the set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MotifMatch {
+    /// Index of the containing glyph in the set.
+    pub glyph: usize,
+    /// Transformation mapping the motif onto the match, 0 for the identity and i + 1 for
+    /// column i of the transformation matrix.
+    pub transformation: usize,
+    pub motif: Glyph,
+}
+
+/// Generated glyphs indexed for containment queries modulo symmetry.
+pub struct GlyphSet<'a> {
+    generator: &'a GlyphGenerator,
+    identifiers: Vec<u64>,
+    canonical: HashMap<u64, usize>,
+}
+
+fn is_subset(a: u64, b: u64) -> bool {
+    a & b == a
+}
+
+impl<'a> GlyphSet<'a> {
+    pub fn new(generator: &'a GlyphGenerator, glyphs: &[Glyph]) -> Self {
+        let internal: Vec<InternalGlyph> = glyphs.iter().map(|g| generator.from_glyph(g)).collect();
+        let canonical = internal
+            .iter()
+            .enumerate()
+            .map(|(i, g)| (generator.canonical_identifier(g), i))
+            .collect();
+        GlyphSet {
+            generator,
+            identifiers: internal.iter().map(|g| g.identifier).collect(),
+            canonical,
+        }
+    }
+
+    fn images(&self, glyph: &Glyph) -> Vec<u64> {
+        self.generator
+            .orbit_identifiers(&self.generator.from_glyph(glyph))
+    }
+
+    fn select(&self, glyph: &Glyph, matches: impl Fn(u64, u64) -> bool) -> Vec<usize> {
+        let images = self.images(glyph);
+        (0..self.identifiers.len())
+            .filter(|&i| {
+                images
+                    .iter()
+                    .any(|&image| matches(image, self.identifiers[i]))
+            })
+            .collect()
+    }
+
+    /// Index of the glyph of the set equal to `glyph` up to symmetry.
+    pub fn position(&self, glyph: &Glyph) -> Option<usize> {
+        let internal = self.generator.from_glyph(glyph);
+        self.canonical
+            .get(&self.generator.canonical_identifier(&internal))
+            .copied()
+    }
+
+    /// Glyphs containing an image of `glyph`, its own symmetry class included.
+    pub fn supersets(&self, glyph: &Glyph) -> Vec<usize> {
+        self.select(glyph, is_subset)
+    }
+
+    /// Glyphs contained in an image of `glyph`, its own symmetry class included.
+    pub fn subsets(&self, glyph: &Glyph) -> Vec<usize> {
+        self.select(glyph, |image, other| is_subset(other, image))
+    }
+
+    /// Glyphs obtained by removing one stroke from `glyph`, up to symmetry.
+    pub fn parents(&self, glyph: &Glyph) -> Vec<usize> {
+        self.select(glyph, |image, other| {
+            is_subset(other, image) && other.count_ones() + 1 == image.count_ones()
+        })
+    }
+
+    /// Glyphs obtained by adding one stroke to `glyph`, up to symmetry.
+    pub fn children(&self, glyph: &Glyph) -> Vec<usize> {
+        self.select(glyph, |image, other| {
+            is_subset(image, other) && image.count_ones() + 1 == other.count_ones()
+        })
+    }
+
+    /// Every distinct image of `motif` contained in a glyph of the set.
+    pub fn find_motif(&self, motif: &Glyph) -> Vec<MotifMatch> {
+        let images = self.images(motif);
+        let mut matches = Vec::new();
+        for (glyph, &identifier) in self.identifiers.iter().enumerate() {
+            for (transformation, &image) in images.iter().enumerate() {
+                if images[..transformation].contains(&image) || !is_subset(image, identifier) {
+                    continue;
+                }
+                matches.push(MotifMatch {
+                    glyph,
+                    transformation,
+                    motif: self
+                        .generator
+                        .to_glyph(&InternalGlyph::from_identifier(image)),
+                });
+            }
+        }
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::setup;
+
+    #[test]
+    fn test_glyph_set_queries() {
+        let context = setup();
+        let generator = context.basic;
+        let strokes = generator.parameters.parent_strokes.clone();
+        let glyphs = generator.generate(&strokes, &strokes[0]);
+        let set = GlyphSet::new(&generator, &glyphs);
+        let (th, d1, d2, bh) = (strokes[0], strokes[2], strokes[3], strokes[5]);
+
+        let corner = Glyph {
+            strokes: vec![th, d1],
+        };
+        let position = set.position(&corner).unwrap();
+        assert_eq!(glyphs[position], corner);
+        assert_eq!(
+            set.position(&Glyph {
+                strokes: vec![d1, d2]
+            }),
+            None
+        );
+
+        let supersets = set.supersets(&corner);
+        assert!(supersets.contains(&position));
+        assert!(supersets.iter().all(|&i| glyphs[i].strokes.len() >= 2));
+
+        let children = set.children(&corner);
+        assert!(!children.is_empty());
+        assert!(children.iter().all(|&i| glyphs[i].strokes.len() == 3));
+        for &child in &children {
+            assert!(set.parents(&glyphs[child]).contains(&position));
+            assert!(supersets.contains(&child));
+        }
+
+        // A single stroke is found in every glyph under some symmetry
+        let single = Glyph { strokes: vec![th] };
+        assert_eq!(set.subsets(&single), vec![set.position(&single).unwrap()]);
+
+        // Two parallel edges, matched as either pair of opposite sides
+        let parallel = Glyph {
+            strokes: vec![th, bh],
+        };
+        let matches = set.find_motif(&parallel);
+        let full = set.position(&Glyph { strokes }).unwrap();
+        assert_eq!(matches.iter().filter(|m| m.glyph == full).count(), 2);
+        for m in &matches {
+            assert!(m
+                .motif
+                .strokes
+                .iter()
+                .all(|s| glyphs[m.glyph].strokes.contains(s)));
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use crate::generate::GlyphGenerator;
+use crate::geometry::{end, point_at, start, BoundingBox, Point, EPSILON};
+use crate::glyph::Glyph;
+use crate::layout::intersection_matrix;
+use crate::parameters::Parameters;
+use crate::stroke::{Stroke, StrokeKind};
+
+/// Maps glyph coordinates, y pointing up, to a square SVG canvas, y pointing down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub frame: BoundingBox,
+    pub size: f64,
+    pub margin: f64,
+}
+
+impl Viewport {
+    pub fn new(frame: BoundingBox, size: f64) -> Self {
+        Viewport {
+            frame,
+            size,
+            margin: size / 10.0,
+        }
+    }
+
+    pub fn scale(&self) -> f64 {
+        let extent = self.frame.width().max(self.frame.height());
+        if extent < EPSILON {
+            return 1.0;
+        }
+        (self.size - 2.0 * self.margin) / extent
+    }
+
+    pub fn map(&self, point: &Point) -> Point {
+        let scale = self.scale();
+        Point::new(
+            self.margin + (point.x - self.frame.min.x) * scale,
+            self.margin + (self.frame.max.y - point.y) * scale,
+        )
+    }
+}
+
+fn format(value: f64) -> String {
+    let rounded = (value * 1000.0).round() / 1000.0;
+    if rounded == 0.0 {
+        "0".to_string()
+    } else {
+        rounded.to_string()
+    }
+}
+
+/// SVG path data drawing the stroke.
+pub fn path_data(stroke: &Stroke, viewport: &Viewport) -> String {
+    let p0 = viewport.map(&start(stroke));
+    let p1 = viewport.map(&end(stroke));
+    let to = format!("{} {}", format(p1.x), format(p1.y));
+    let segment = match stroke.kind {
+        StrokeKind::Arc { bulge } if bulge.abs() >= EPSILON => {
+            let chord = p0.distance(&p1);
+            let radius = chord * (1.0 + bulge * bulge) / (4.0 * bulge.abs());
+            // The y axis is flipped, so counterclockwise arcs have a negative sweep
+            let large_arc = (bulge.abs() > 1.0) as u8;
+            let sweep = (bulge < 0.0) as u8;
+            let radius = format(radius);
+            format!("A {radius} {radius} 0 {large_arc} {sweep} {to}")
+        }
+        StrokeKind::Quadratic { cx, cy } => {
+            let c = viewport.map(&Point::new(cx, cy));
+            format!("Q {} {} {to}", format(c.x), format(c.y))
+        }
+        StrokeKind::Cubic { cx0, cy0, cx1, cy1 } => {
+            let c0 = viewport.map(&Point::new(cx0, cy0));
+            let c1 = viewport.map(&Point::new(cx1, cy1));
+            format!(
+                "C {} {} {} {} {to}",
+                format(c0.x),
+                format(c0.y),
+                format(c1.x),
+                format(c1.y)
+            )
+        }
+        _ => format!("L {to}"),
+    };
+    format!("M {} {} {segment}", format(p0.x), format(p0.y))
+}
+
+pub fn glyph_to_svg(glyph: &Glyph, viewport: &Viewport) -> String {
+    let size = format(viewport.size);
+    let paths: String = glyph
+        .strokes
+        .iter()
+        .map(|stroke| format!("<path d=\"{}\"/>", path_data(stroke, viewport)))
+        .collect();
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" viewBox=\"0 0 {size} {size}\">\
+         <g fill=\"none\" stroke=\"black\" stroke-width=\"{}\" stroke-linecap=\"round\">{paths}</g></svg>",
+        format(viewport.size / 25.0)
+    )
+}
+
+fn svg_document(size: f64, content: &str) -> String {
+    let size = format(size);
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" viewBox=\"0 0 {size} {size}\">{content}</svg>"
+    )
+}
+
+fn stroke_group(strokes: &[&Stroke], attributes: &str, viewport: &Viewport) -> String {
+    if strokes.is_empty() {
+        return String::new();
+    }
+    let paths: String = strokes
+        .iter()
+        .map(|stroke| format!("<path d=\"{}\"/>", path_data(stroke, viewport)))
+        .collect();
+    format!("<g fill=\"none\" {attributes}>{paths}</g>")
+}
+
+/// Debug drawing of the parameters: anchors and parent strokes with their indices. With a
+/// selected stroke, drawn in blue, the strokes the intersection matrix links to it are red,
+/// dashed when the geometry disagrees, strokes only the geometry links to it are dashed
+/// orange, and its images under the transformation matrix are dotted green.
+pub fn parameters_to_svg(
+    parameters: &Parameters,
+    selected: Option<usize>,
+    viewport: &Viewport,
+) -> String {
+    let strokes = &parameters.parent_strokes;
+    let width = viewport.size / 200.0;
+    let font = format(viewport.size / 40.0);
+    let mut content = String::new();
+
+    let all: Vec<&Stroke> = strokes.iter().collect();
+    content += &stroke_group(
+        &all,
+        &format!("stroke=\"#ccc\" stroke-width=\"{}\"", format(width)),
+        viewport,
+    );
+
+    if let Some(i) = selected {
+        let geometry = intersection_matrix(strokes);
+        let linked = |matrix: &[Vec<u8>], j: usize| j != i && matrix[i][j] != 0;
+        let pick = |filter: &dyn Fn(usize) -> bool| -> Vec<&Stroke> {
+            (0..strokes.len())
+                .filter(|&j| filter(j))
+                .map(|j| &strokes[j])
+                .collect()
+        };
+        let matrix = &parameters.intersection_matrix;
+        let highlight = format!("stroke-width=\"{}\"", format(2.0 * width));
+        content += &stroke_group(
+            &pick(&|j| linked(matrix, j) && linked(&geometry, j)),
+            &format!("stroke=\"red\" {highlight}"),
+            viewport,
+        );
+        content += &stroke_group(
+            &pick(&|j| linked(matrix, j) && !linked(&geometry, j)),
+            &format!("stroke=\"red\" stroke-dasharray=\"4 4\" {highlight}"),
+            viewport,
+        );
+        content += &stroke_group(
+            &pick(&|j| !linked(matrix, j) && linked(&geometry, j)),
+            &format!("stroke=\"orange\" stroke-dasharray=\"4 4\" {highlight}"),
+            viewport,
+        );
+        // Parameters without symmetries have no transformation rows
+        let mut images = parameters
+            .transformation_matrix
+            .get(i)
+            .cloned()
+            .unwrap_or_default();
+        images.sort();
+        images.dedup();
+        let images: Vec<&Stroke> = images
+            .into_iter()
+            .filter(|&j| j != i)
+            .filter_map(|j| strokes.get(j))
+            .collect();
+        content += &stroke_group(
+            &images,
+            &format!("stroke=\"green\" stroke-dasharray=\"1 3\" {highlight}"),
+            viewport,
+        );
+        content += &stroke_group(
+            &[&strokes[i]],
+            &format!("stroke=\"blue\" {highlight}"),
+            viewport,
+        );
+    }
+
+    let anchors: String = parameters
+        .anchors()
+        .iter()
+        .enumerate()
+        .map(|(k, anchor)| {
+            let p = viewport.map(anchor);
+            format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\"/><text x=\"{}\" y=\"{}\">{k}</text>",
+                format(p.x),
+                format(p.y),
+                format(2.0 * width),
+                format(p.x + 3.0 * width),
+                format(p.y - 3.0 * width)
+            )
+        })
+        .collect();
+    content += &format!("<g fill=\"black\" font-size=\"{font}\">{anchors}</g>");
+
+    let labels: String = strokes
+        .iter()
+        .enumerate()
+        .map(|(k, stroke)| {
+            let p = viewport.map(&point_at(stroke, 0.5));
+            format!(
+                "<text x=\"{}\" y=\"{}\">{k}</text>",
+                format(p.x),
+                format(p.y)
+            )
+        })
+        .collect();
+    content +=
+        &format!("<g fill=\"#666\" font-size=\"{font}\" text-anchor=\"middle\">{labels}</g>");
+
+    svg_document(viewport.size, &content)
+}
+
+impl GlyphGenerator {
+    /// Renders a glyph as SVG, framed by the parent strokes.
+    pub fn render(&self, glyph: &Glyph, size: f64) -> String {
+        let frame = BoundingBox::from_strokes(&self.parameters.parent_strokes)
+            .expect("Parameters should contain parent strokes");
+        glyph_to_svg(glyph, &Viewport::new(frame, size))
+    }
+
+    /// Renders the parameters for debugging, see `parameters_to_svg`.
+    pub fn render_parameters(&self, selected: Option<usize>, size: f64) -> String {
+        let frame = BoundingBox::from_strokes(&self.parameters.parent_strokes)
+            .expect("Parameters should contain parent strokes");
+        parameters_to_svg(&self.parameters, selected, &Viewport::new(frame, size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::stroke_intersections;
+
+    fn viewport() -> Viewport {
+        Viewport::new(
+            BoundingBox {
+                min: Point::new(-1.0, -1.0),
+                max: Point::new(1.0, 1.0),
+            },
+            100.0,
+        )
+    }
+
+    #[test]
+    fn test_path_data() {
+        let viewport = viewport();
+        assert_eq!(
+            path_data(&Stroke::line(-1.0, -1.0, 1.0, 1.0), &viewport),
+            "M 10 90 L 90 10"
+        );
+        assert_eq!(
+            path_data(&Stroke::quadratic(-1.0, 0.0, 0.0, 1.0, 1.0, 0.0), &viewport),
+            "M 10 50 Q 50 10 90 50"
+        );
+        assert_eq!(
+            path_data(&Stroke::arc(-1.0, 0.0, 1.0, 0.0, 1.0), &viewport),
+            "M 10 50 A 40 40 0 0 0 90 50"
+        );
+    }
+
+    #[test]
+    fn test_glyph_to_svg() {
+        let glyph = Glyph {
+            strokes: vec![
+                Stroke::line(-1.0, -1.0, 1.0, 1.0),
+                Stroke::cubic(-1.0, 1.0, 0.0, 1.0, 0.0, -1.0, 1.0, -1.0),
+            ],
+        };
+        let svg = glyph_to_svg(&glyph, &viewport());
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("<path").count(), 2);
+        assert!(svg.contains("C 50 10 50 90 90 90"));
+    }
+
+    #[test]
+    fn test_parameters_to_svg() {
+        let parameters =
+            Parameters::from_layout(&crate::layout::Layout::Square { size: 2 }).unwrap();
+        let svg = parameters_to_svg(&parameters, None, &viewport());
+        assert_eq!(svg.matches("<circle").count(), 4);
+        assert_eq!(svg.matches("<path").count(), 6);
+        assert_eq!(svg.matches("<text").count(), 4 + 6);
+        assert!(!svg.contains("red"));
+
+        // Stroke 0 joins anchors 0 and 1, which every other stroke but the opposite side meets
+        let mut edited = parameters.clone();
+        let opposite = edited
+            .parent_strokes
+            .iter()
+            .position(|s| stroke_intersections(s, &parameters.parent_strokes[0]).is_empty())
+            .unwrap();
+        edited.intersection_matrix[0][opposite] = 1;
+        edited.intersection_matrix[0][1] = 0;
+        let svg = parameters_to_svg(&edited, Some(0), &viewport());
+        assert!(svg.contains("stroke=\"blue\""));
+        // Three linked strokes, the opposite side, stroke 1, three images and stroke 0
+        assert_eq!(svg.matches("<path").count(), 6 + 3 + 1 + 1 + 3 + 1);
+        assert!(svg.contains("stroke=\"red\" stroke-dasharray"));
+        assert!(svg.contains("stroke=\"orange\""));
+
+        edited.transformation_matrix.clear();
+        let svg = parameters_to_svg(&edited, Some(0), &viewport());
+        assert_eq!(svg.matches("<path").count(), 6 + 3 + 1 + 1 + 1);
+    }
+}
@@ -0,0 +1,205 @@
+use std::fmt;
+
+use crate::parameters::Parameters;
+use crate::Computable;
+use schemars::{schema_for, JsonSchema};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+pub const PARAMETERS_VERSION: u32 = 1;
+pub const COMPUTABLE_VERSION: u32 = 1;
+
+pub(crate) fn parameters_version() -> u32 {
+    PARAMETERS_VERSION
+}
+
+pub(crate) fn computable_version() -> u32 {
+    COMPUTABLE_VERSION
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    Json(String),
+    UnsupportedVersion { found: u64, supported: u32 },
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaError::Json(error) => write!(f, "Invalid document: {error}"),
+            SchemaError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "Document version {found} is newer than the supported version {supported}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+fn parse(json: &str) -> Result<Value, SchemaError> {
+    serde_json::from_str(json).map_err(|e| SchemaError::Json(e.to_string()))
+}
+
+/// Reads a document of version `supported`, or of version 0 when it has no `version`
+/// field: the format predating versioning, which differs only by lacking the field.
+fn read_versioned<T: DeserializeOwned>(mut value: Value, supported: u32) -> Result<T, SchemaError> {
+    let found = match value.get("version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| SchemaError::Json(format!("version {version} is not an integer")))?,
+    };
+    if found > supported as u64 {
+        return Err(SchemaError::UnsupportedVersion { found, supported });
+    }
+    if let Value::Object(object) = &mut value {
+        object.insert("version".to_string(), Value::from(supported));
+    }
+    serde_json::from_value(value).map_err(|e| SchemaError::Json(e.to_string()))
+}
+
+fn schema<T: JsonSchema>() -> String {
+    serde_json::to_string_pretty(&schema_for!(T)).unwrap()
+}
+
+pub fn parameters_schema() -> String {
+    schema::<Parameters>()
+}
+
+pub fn computable_schema() -> String {
+    schema::<Computable>()
+}
+
+impl Parameters {
+    /// Parameters from a JSON document of any supported version.
+    pub fn from_json(json: &str) -> Result<Self, SchemaError> {
+        Self::from_value(parse(json)?)
+    }
+
+    /// Same as `from_json`, from a parsed document.
+    pub fn from_value(value: Value) -> Result<Self, SchemaError> {
+        let parameters: Parameters = read_versioned(value, PARAMETERS_VERSION)?;
+        parameters
+            .check_size()
+            .map_err(|e| SchemaError::Json(e.to_string()))?;
+        Ok(parameters)
+    }
+}
+
+impl Computable {
+    /// Computable from a JSON document of any supported version.
+    pub fn from_json(json: &str) -> Result<Self, SchemaError> {
+        Self::from_value(parse(json)?)
+    }
+
+    /// Same as `from_json`, from a parsed document.
+    pub fn from_value(value: Value) -> Result<Self, SchemaError> {
+        read_versioned(value, COMPUTABLE_VERSION)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// TypeScript declarations of the types the JavaScript exports take and return.
+    fn typescript_types() -> String {
+        use crate::constraints::Constraints;
+        use crate::contact::Contact;
+        use crate::glyph::Glyph;
+        use crate::normalize::{Normalization, NormalizedGlyph};
+        use crate::stroke::{Stroke, StrokeKind, StrokeRef};
+        use ts_rs::TS;
+
+        fn declaration<T: TS>() -> String {
+            format!("{}export {}\n", T::docs().unwrap_or_default(), T::decl())
+        }
+        [
+            declaration::<StrokeKind>(),
+            declaration::<Stroke>(),
+            declaration::<StrokeRef>(),
+            declaration::<Glyph>(),
+            declaration::<Parameters>(),
+            declaration::<Contact>(),
+            declaration::<Constraints>(),
+            declaration::<Normalization>(),
+            declaration::<NormalizedGlyph>(),
+            declaration::<Computable>(),
+        ]
+        .join("\n")
+    }
+
+    #[test]
+    fn test_published_schemas() {
+        // Regenerate the files from these functions when the formats change
+        assert_eq!(
+            parameters_schema(),
+            include_str!("../schemas/parameters.schema.json").trim_end()
+        );
+        assert_eq!(
+            computable_schema(),
+            include_str!("../schemas/computable.schema.json").trim_end()
+        );
+        assert_eq!(typescript_types(), include_str!("../schemas/types.d.ts"));
+    }
+
+    #[test]
+    fn test_versions() {
+        let legacy = include_str!("../tests/parameters_4ap.json");
+        let parameters = Parameters::from_json(legacy).unwrap();
+        assert_eq!(parameters.version, PARAMETERS_VERSION);
+        assert_eq!(parameters.parent_strokes.len(), 6);
+
+        let current = serde_json::to_string(&parameters).unwrap();
+        assert!(current.contains("\"version\":1"));
+        assert_eq!(Parameters::from_json(&current).unwrap(), parameters);
+
+        let future = current.replace("\"version\":1", "\"version\":2");
+        assert_eq!(
+            Parameters::from_json(&future),
+            Err(SchemaError::UnsupportedVersion {
+                found: 2,
+                supported: PARAMETERS_VERSION
+            })
+        );
+        assert!(matches!(
+            Parameters::from_json("{}"),
+            Err(SchemaError::Json(_))
+        ));
+        let mut oversized = parameters.clone();
+        oversized.parent_strokes = vec![oversized.parent_strokes[0]; 65];
+        assert_eq!(
+            Parameters::from_json(&serde_json::to_string(&oversized).unwrap()),
+            Err(SchemaError::Json(
+                "65 parent strokes, at most 64 are supported".to_string()
+            ))
+        );
+
+        let computable = Computable::from_json("{\"strokes\":[[0,1]]}").unwrap();
+        assert_eq!(computable.version, COMPUTABLE_VERSION);
+        for version in ["\"1\"", "1.5", "-1", "null"] {
+            let json = format!("{{\"version\":{version},\"strokes\":[[0,1]]}}");
+            assert_eq!(
+                Computable::from_json(&json).err(),
+                Some(SchemaError::Json(format!(
+                    "version {version} is not an integer"
+                )))
+            );
+        }
+
+        // Parsed documents go through the same checks
+        let value: Value = serde_json::from_str(legacy).unwrap();
+        assert_eq!(Parameters::from_value(value).unwrap(), parameters);
+        assert_eq!(
+            Parameters::from_value(serde_json::from_str(&future).unwrap()),
+            Err(SchemaError::UnsupportedVersion {
+                found: 2,
+                supported: PARAMETERS_VERSION
+            })
+        );
+        let computable =
+            Computable::from_value(serde_json::json!({"version": 0, "strokes": [[0, 1]]})).unwrap();
+        assert_eq!(computable.version, COMPUTABLE_VERSION);
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+use crate::constraints::ConstraintMasks;
+use crate::generate::{Generation, GlyphGenerator};
+use crate::glyph::{Glyph, InternalGlyph};
+use crate::normalize::Normalization;
+use crate::stroke::{Stroke, StrokeKey};
+
+/// Generation run in slices, yielding each level as soon as it is complete.
+#[derive(Debug, Clone)]
+pub struct Stepper {
+    generation: Generation,
+    normalization: Normalization,
+    /// Drawing keys of the glyphs yielded so far, when normalizing.
+    drawings: HashSet<Vec<StrokeKey>>,
+    pending: Vec<InternalGlyph>,
+}
+
+impl Stepper {
+    pub fn is_done(&self) -> bool {
+        self.generation.is_done() && self.pending.is_empty()
+    }
+
+    /// Expands glyphs until `should_stop` returns true, checked after each glyph, and returns
+    /// the glyphs of the levels completed meanwhile. Concatenated, the returned glyphs are
+    /// the glyphs of `generate`, constrained and normalized as the stepper was built.
+    pub fn step(
+        &mut self,
+        generator: &GlyphGenerator,
+        mut should_stop: impl FnMut() -> bool,
+    ) -> Vec<Glyph> {
+        let pending = &mut self.pending;
+        // Stopping only ends the slice, the next step picks up where it stopped
+        let _ = self.generation.run(
+            generator,
+            |_, _, _, _| {},
+            |generation, completed| {
+                if completed {
+                    pending.extend_from_slice(generation.level());
+                }
+                if should_stop() {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            },
+        );
+        let masks = self.generation.masks();
+        let glyphs: Vec<Glyph> = self
+            .pending
+            .drain(..)
+            .filter_map(|g| generator.admissible(&g, masks))
+            .map(|g| generator.to_glyph(&g))
+            .collect();
+        if self.normalization == Normalization::None {
+            return glyphs;
+        }
+        glyphs
+            .into_iter()
+            .filter(|glyph| {
+                self.drawings
+                    .insert(generator.drawing_key(glyph, self.normalization))
+            })
+            .collect()
+    }
+}
+
+impl GlyphGenerator {
+    pub fn stepper(&self, strokes: &[Stroke], seed: &Stroke) -> Stepper {
+        self.stepper_constrained(
+            strokes,
+            seed,
+            &ConstraintMasks::default(),
+            Normalization::None,
+        )
+    }
+
+    /// Stepper yielding the glyphs of `generate_constrained`, normalized.
+    pub fn stepper_constrained(
+        &self,
+        strokes: &[Stroke],
+        seed: &Stroke,
+        masks: &ConstraintMasks,
+        normalization: Normalization,
+    ) -> Stepper {
+        let generation = Generation::new(self, strokes, seed, masks.clone());
+        Stepper {
+            pending: generation.level().to_vec(),
+            generation,
+            normalization,
+            drawings: HashSet::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::Constraints;
+    use crate::stroke::StrokeRef;
+    use crate::tests::setup;
+
+    #[test]
+    fn test_stepper() {
+        let context = setup();
+        let generator = context.basic;
+        let strokes = generator.parameters.parent_strokes.clone();
+        let expected = generator.generate(&strokes, &strokes[0]);
+
+        // One glyph per step
+        let mut stepper = generator.stepper(&strokes, &strokes[0]);
+        let mut glyphs: Vec<Glyph> = Vec::new();
+        let mut steps = 0;
+        while !stepper.is_done() {
+            glyphs.extend(stepper.step(&generator, || true));
+            steps += 1;
+        }
+        assert_eq!(glyphs, expected);
+        assert!(steps > strokes.len());
+        assert!(stepper.step(&generator, || true).is_empty());
+
+        let mut stepper = generator.stepper(&strokes, &strokes[0]);
+        assert_eq!(stepper.step(&generator, || false), expected);
+        assert!(stepper.is_done());
+
+        let constraints = Constraints {
+            forbidden: vec![StrokeRef::Stroke(strokes[5])],
+            ..Default::default()
+        };
+        for normalization in [Normalization::None, Normalization::Planar] {
+            let expected = generator.normalize(
+                &generator
+                    .generate_constrained(&strokes, &strokes[0], &constraints)
+                    .unwrap(),
+                normalization,
+            );
+            let masks = generator.constraint_masks(&constraints).unwrap();
+            let mut stepper =
+                generator.stepper_constrained(&strokes, &strokes[0], &masks, normalization);
+            let mut glyphs: Vec<Glyph> = Vec::new();
+            while !stepper.is_done() {
+                glyphs.extend(stepper.step(&generator, || true));
+            }
+            assert_eq!(glyphs, expected);
+        }
+    }
+}
@@ -1,28 +1,224 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+/// Grid step coordinates are snapped to; values straddling a cell boundary may snap apart.
+pub const DEFAULT_QUANTUM: f64 = 1e-9;
+
+/// Snapped endpoint coordinates, kind tag and snapped kind values padded with zeros.
+pub type StrokeKey = [i64; 9];
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StrokeKind {
+    #[default]
+    Line,
+    /// Circular arc, `bulge` being the tangent of a quarter of the swept angle,
+    /// positive when the arc turns counterclockwise.
+    Arc { bulge: f64 },
+    /// Quadratic Bézier curve with control point (cx, cy).
+    Quadratic { cx: f64, cy: f64 },
+    /// Cubic Bézier curve with control points (cx0, cy0) and (cx1, cy1).
+    Cubic {
+        cx0: f64,
+        cy0: f64,
+        cx1: f64,
+        cy1: f64,
+    },
+}
+
+impl StrokeKind {
+    pub fn is_line(&self) -> bool {
+        matches!(self, StrokeKind::Line)
+    }
+
+    pub(crate) fn tag(&self) -> u8 {
+        match self {
+            StrokeKind::Line => 0,
+            StrokeKind::Arc { .. } => 1,
+            StrokeKind::Quadratic { .. } => 2,
+            StrokeKind::Cubic { .. } => 3,
+        }
+    }
+
+    pub fn values(&self) -> Vec<f64> {
+        match *self {
+            StrokeKind::Line => vec![],
+            StrokeKind::Arc { bulge } => vec![bulge],
+            StrokeKind::Quadratic { cx, cy } => vec![cx, cy],
+            StrokeKind::Cubic { cx0, cy0, cx1, cy1 } => vec![cx0, cy0, cx1, cy1],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(test, derive(ts_rs::TS))]
 pub struct Stroke {
     pub x0: f64,
     pub y0: f64,
     pub x1: f64,
     pub y1: f64,
+    #[serde(default, skip_serializing_if = "StrokeKind::is_line")]
+    #[cfg_attr(test, ts(as = "Option<StrokeKind>", optional))]
+    pub kind: StrokeKind,
 }
 
 impl Stroke {
+    pub fn line(x0: f64, y0: f64, x1: f64, y1: f64) -> Self {
+        Stroke {
+            x0,
+            y0,
+            x1,
+            y1,
+            kind: StrokeKind::Line,
+        }
+    }
+
+    pub fn arc(x0: f64, y0: f64, x1: f64, y1: f64, bulge: f64) -> Self {
+        Stroke {
+            kind: StrokeKind::Arc { bulge },
+            ..Stroke::line(x0, y0, x1, y1)
+        }
+    }
+
+    pub fn quadratic(x0: f64, y0: f64, cx: f64, cy: f64, x1: f64, y1: f64) -> Self {
+        Stroke {
+            kind: StrokeKind::Quadratic { cx, cy },
+            ..Stroke::line(x0, y0, x1, y1)
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn cubic(
+        x0: f64,
+        y0: f64,
+        cx0: f64,
+        cy0: f64,
+        cx1: f64,
+        cy1: f64,
+        x1: f64,
+        y1: f64,
+    ) -> Self {
+        Stroke {
+            kind: StrokeKind::Cubic { cx0, cy0, cx1, cy1 },
+            ..Stroke::line(x0, y0, x1, y1)
+        }
+    }
+
+    /// The same drawing, travelled from the other end.
+    pub fn reversed(&self) -> Stroke {
+        let kind = match self.kind {
+            StrokeKind::Line => StrokeKind::Line,
+            StrokeKind::Arc { bulge } => StrokeKind::Arc { bulge: -bulge },
+            StrokeKind::Quadratic { cx, cy } => StrokeKind::Quadratic { cx, cy },
+            StrokeKind::Cubic { cx0, cy0, cx1, cy1 } => StrokeKind::Cubic {
+                cx0: cx1,
+                cy0: cy1,
+                cx1: cx0,
+                cy1: cy0,
+            },
+        };
+        Stroke {
+            x0: self.x1,
+            y0: self.y1,
+            x1: self.x0,
+            y1: self.y0,
+            kind,
+        }
+    }
+
+    /// Image of the stroke under the linear map `matrix`, assumed to be a rotation or a reflection.
+    pub fn transformed(&self, matrix: [[f64; 2]; 2]) -> Stroke {
+        let apply = |x: f64, y: f64| {
+            (
+                matrix[0][0] * x + matrix[0][1] * y,
+                matrix[1][0] * x + matrix[1][1] * y,
+            )
+        };
+        let (x0, y0) = apply(self.x0, self.y0);
+        let (x1, y1) = apply(self.x1, self.y1);
+        let kind = match self.kind {
+            StrokeKind::Line => StrokeKind::Line,
+            StrokeKind::Arc { bulge } => {
+                let determinant = matrix[0][0] * matrix[1][1] - matrix[0][1] * matrix[1][0];
+                StrokeKind::Arc {
+                    bulge: bulge * determinant.signum(),
+                }
+            }
+            StrokeKind::Quadratic { cx, cy } => {
+                let (cx, cy) = apply(cx, cy);
+                StrokeKind::Quadratic { cx, cy }
+            }
+            StrokeKind::Cubic { cx0, cy0, cx1, cy1 } => {
+                let (cx0, cy0) = apply(cx0, cy0);
+                let (cx1, cy1) = apply(cx1, cy1);
+                StrokeKind::Cubic { cx0, cy0, cx1, cy1 }
+            }
+        };
+        Stroke {
+            x0,
+            y0,
+            x1,
+            y1,
+            kind,
+        }
+    }
+
+    pub fn translated(&self, dx: f64, dy: f64) -> Stroke {
+        let kind = match self.kind {
+            StrokeKind::Quadratic { cx, cy } => StrokeKind::Quadratic {
+                cx: cx + dx,
+                cy: cy + dy,
+            },
+            StrokeKind::Cubic { cx0, cy0, cx1, cy1 } => StrokeKind::Cubic {
+                cx0: cx0 + dx,
+                cy0: cy0 + dy,
+                cx1: cx1 + dx,
+                cy1: cy1 + dy,
+            },
+            kind => kind,
+        };
+        Stroke {
+            x0: self.x0 + dx,
+            y0: self.y0 + dy,
+            x1: self.x1 + dx,
+            y1: self.y1 + dy,
+            kind,
+        }
+    }
+
+    /// Coordinates snapped to a grid of step `quantum`, in the direction of travel.
+    pub fn directed_key(&self, quantum: f64) -> StrokeKey {
+        let snap = |v: f64| (v / quantum).round() as i64;
+        let mut key = [0; 9];
+        key[0] = snap(self.x0);
+        key[1] = snap(self.y0);
+        key[2] = snap(self.x1);
+        key[3] = snap(self.y1);
+        key[4] = self.kind.tag() as i64;
+        for (slot, value) in key[5..].iter_mut().zip(self.kind.values()) {
+            *slot = snap(value);
+        }
+        key
+    }
+
+    /// Direction-insensitive coordinates snapped to a grid of step `quantum`. Two strokes
+    /// are equal, and hash identically, when their keys for `DEFAULT_QUANTUM` are equal.
+    pub fn key(&self, quantum: f64) -> StrokeKey {
+        self.directed_key(quantum)
+            .min(self.reversed().directed_key(quantum))
+    }
+
+    /// Whether `other` draws the same thing as this stroke, travelled in the opposite direction.
     pub fn is_inverse(&self, other: &Stroke) -> bool {
-        self.x0 == other.x1 && self.y0 == other.y1 && self.x1 == other.x0 && self.y1 == other.y0
+        self.directed_key(DEFAULT_QUANTUM) == other.reversed().directed_key(DEFAULT_QUANTUM)
     }
 }
 
 impl PartialEq for Stroke {
     fn eq(&self, other: &Self) -> bool {
-        const TOLERANCE: f64 = 1e-16; // Define a suitable tolerance
-
-        (self.x0 - other.x0).abs() < TOLERANCE
-            && (self.y0 - other.y0).abs() < TOLERANCE
-            && (self.x1 - other.x1).abs() < TOLERANCE
-            && (self.y1 - other.y1).abs() < TOLERANCE
+        self.key(DEFAULT_QUANTUM) == other.key(DEFAULT_QUANTUM)
     }
 }
 
@@ -30,23 +226,206 @@ impl Eq for Stroke {}
 
 impl Hash for Stroke {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        const SCALE: f64 = 1e16; // Scale factor for discretization
-
-        // Discretize or round the f64 fields
-        let x0 = (self.x0 * SCALE).round() as i64;
-        let y0 = (self.y0 * SCALE).round() as i64;
-        let x1 = (self.x1 * SCALE).round() as i64;
-        let y1 = (self.y1 * SCALE).round() as i64;
-
-        // Hash the discretized values
-        x0.hash(state);
-        y0.hash(state);
-        x1.hash(state);
-        y1.hash(state);
+        self.key(DEFAULT_QUANTUM).hash(state);
     }
 }
 
+/// A stroke given either by the indices of its two anchors, as `[a, b]`, or by its geometry.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(untagged)]
+pub enum StrokeRef {
+    Anchors([usize; 2]),
+    Stroke(Stroke),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct InternalStroke {
     pub index: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_line_json_is_unchanged() {
+        let stroke: Stroke =
+            serde_json::from_str("{\"x0\":-1.0,\"y0\":-1.0,\"x1\":1.0,\"y1\":1.0}").unwrap();
+        assert_eq!(stroke, Stroke::line(-1.0, -1.0, 1.0, 1.0));
+        assert_eq!(
+            serde_json::to_string(&stroke).unwrap(),
+            "{\"x0\":-1.0,\"y0\":-1.0,\"x1\":1.0,\"y1\":1.0}"
+        );
+    }
+
+    #[test]
+    fn test_curve_json_round_trip() {
+        let strokes = [
+            Stroke::arc(-1.0, 0.0, 1.0, 0.0, 0.5),
+            Stroke::quadratic(-1.0, 0.0, 0.0, 1.0, 1.0, 0.0),
+            Stroke::cubic(-1.0, 0.0, -0.5, 1.0, 0.5, -1.0, 1.0, 0.0),
+        ];
+        for stroke in strokes {
+            let json = serde_json::to_string(&stroke).unwrap();
+            assert_eq!(serde_json::from_str::<Stroke>(&json).unwrap(), stroke);
+        }
+        let arc: Stroke = serde_json::from_str(
+            "{\"x0\":-1,\"y0\":0,\"x1\":1,\"y1\":0,\"kind\":{\"type\":\"arc\",\"bulge\":1}}",
+        )
+        .unwrap();
+        assert_eq!(arc, Stroke::arc(-1.0, 0.0, 1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_curve_equality_and_inverse() {
+        let line = Stroke::line(-1.0, 0.0, 1.0, 0.0);
+        let arc = Stroke::arc(-1.0, 0.0, 1.0, 0.0, 0.5);
+        let cubic = Stroke::cubic(-1.0, 0.0, -0.5, 1.0, 0.5, -1.0, 1.0, 0.0);
+        assert_ne!(line, arc);
+        assert_ne!(arc, Stroke::arc(-1.0, 0.0, 1.0, 0.0, -0.5));
+        assert!(arc.is_inverse(&Stroke::arc(1.0, 0.0, -1.0, 0.0, -0.5)));
+        assert!(!arc.is_inverse(&Stroke::arc(1.0, 0.0, -1.0, 0.0, 0.5)));
+        assert!(cubic.is_inverse(&Stroke::cubic(1.0, 0.0, 0.5, -1.0, -0.5, 1.0, -1.0, 0.0)));
+    }
+
+    #[test]
+    fn test_transformed() {
+        let flip_vertical = [[1.0, 0.0], [0.0, -1.0]];
+        let arc = Stroke::arc(-1.0, 0.0, 1.0, 0.0, 0.5);
+        assert_eq!(
+            arc.transformed(flip_vertical),
+            Stroke::arc(-1.0, 0.0, 1.0, 0.0, -0.5)
+        );
+        let quadratic = Stroke::quadratic(-1.0, 0.0, 0.0, 1.0, 1.0, 0.0);
+        assert_eq!(
+            quadratic.transformed(flip_vertical),
+            Stroke::quadratic(-1.0, 0.0, 0.0, -1.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_equality_tolerates_arithmetic_noise() {
+        let stroke = Stroke::line(0.1 + 0.2, -1.0, 1.0, 0.7);
+        assert_eq!(stroke, Stroke::line(0.3, -1.0, 1.0, 0.7));
+        assert_eq!(stroke, Stroke::line(1.0, 0.7, 0.3, -1.0));
+        assert!(stroke.is_inverse(&Stroke::line(1.0, 0.7, 0.3, -1.0)));
+        assert!(!stroke.is_inverse(&stroke));
+        // Large coordinates no longer overflow
+        assert_ne!(
+            Stroke::line(1e6, 0.0, 2e6, 0.0),
+            Stroke::line(1e6, 0.0, 3e6, 0.0)
+        );
+    }
+
+    fn hash(stroke: &Stroke) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+        let mut hasher = DefaultHasher::new();
+        stroke.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn coordinate() -> impl Strategy<Value = f64> {
+        // Grid values, as anchors are in practice
+        (-2000i32..2000).prop_map(|v| v as f64 / 4.0)
+    }
+
+    fn boundary_coordinate() -> impl Strategy<Value = f64> {
+        // Grid values moved next to the boundary between two cells
+        (coordinate(), -0.25..0.25f64).prop_map(|(v, jitter)| v + (0.5 + jitter) * DEFAULT_QUANTUM)
+    }
+
+    fn kind() -> impl Strategy<Value = StrokeKind> {
+        prop_oneof![
+            Just(StrokeKind::Line),
+            coordinate().prop_map(|bulge| StrokeKind::Arc { bulge }),
+            (coordinate(), coordinate()).prop_map(|(cx, cy)| StrokeKind::Quadratic { cx, cy }),
+            (coordinate(), coordinate(), coordinate(), coordinate())
+                .prop_map(|(cx0, cy0, cx1, cy1)| StrokeKind::Cubic { cx0, cy0, cx1, cy1 }),
+        ]
+    }
+
+    fn stroke() -> impl Strategy<Value = Stroke> {
+        (
+            coordinate(),
+            coordinate(),
+            coordinate(),
+            coordinate(),
+            kind(),
+        )
+            .prop_map(|(x0, y0, x1, y1, kind)| Stroke {
+                x0,
+                y0,
+                x1,
+                y1,
+                kind,
+            })
+    }
+
+    fn perturbed(stroke: &Stroke, noise: f64) -> Stroke {
+        let kind = match stroke.kind {
+            StrokeKind::Line => StrokeKind::Line,
+            StrokeKind::Arc { bulge } => StrokeKind::Arc {
+                bulge: bulge - noise,
+            },
+            StrokeKind::Quadratic { cx, cy } => StrokeKind::Quadratic {
+                cx: cx + noise,
+                cy: cy - noise,
+            },
+            StrokeKind::Cubic { cx0, cy0, cx1, cy1 } => StrokeKind::Cubic {
+                cx0: cx0 + noise,
+                cy0: cy0 - noise,
+                cx1: cx1 + noise,
+                cy1: cy1 - noise,
+            },
+        };
+        Stroke {
+            x0: stroke.x0 + noise,
+            y0: stroke.y0 - noise,
+            x1: stroke.x1 + noise,
+            y1: stroke.y1 - noise,
+            kind,
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_equal_strokes_hash_equally(a in stroke(), b in stroke()) {
+            if a == b {
+                prop_assert_eq!(hash(&a), hash(&b));
+            }
+        }
+
+        #[test]
+        fn prop_noisy_strokes_are_equal(a in stroke(), noise in -1e-12..1e-12f64) {
+            let b = perturbed(&a, noise);
+            prop_assert_eq!(a, b);
+            prop_assert_eq!(hash(&a), hash(&b));
+        }
+
+        #[test]
+        fn prop_boundary_noise_moves_to_neighbouring_cells(
+            (x0, y0, x1, y1) in (boundary_coordinate(), boundary_coordinate(), boundary_coordinate(), boundary_coordinate()),
+            noise in -1e-12..1e-12f64,
+        ) {
+            let a = Stroke::line(x0, y0, x1, y1);
+            let b = perturbed(&a, noise);
+            let (ka, kb) = (a.directed_key(DEFAULT_QUANTUM), b.directed_key(DEFAULT_QUANTUM));
+            prop_assert!(ka.iter().zip(kb).all(|(u, v)| (u - v).abs() <= 1));
+            // Equality and hashing stay consistent with the keys, whichever cell is chosen
+            prop_assert_eq!(a == b, a.key(DEFAULT_QUANTUM) == b.key(DEFAULT_QUANTUM));
+            if a == b {
+                prop_assert_eq!(hash(&a), hash(&b));
+            }
+        }
+
+        #[test]
+        fn prop_equality_ignores_direction(a in stroke(), noise in -1e-12..1e-12f64) {
+            let b = perturbed(&a.reversed(), noise);
+            prop_assert_eq!(a, b);
+            prop_assert_eq!(hash(&a), hash(&b));
+            prop_assert_eq!(a.is_inverse(&b), a.directed_key(DEFAULT_QUANTUM) != b.directed_key(DEFAULT_QUANTUM));
+        }
+    }
+}
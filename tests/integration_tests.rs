@@ -1,6 +1,12 @@
 #[cfg(test)]
 mod tests {
-    use glyphs_generator::{compute, initialize};
+    use glyphs_generator::glyph::Glyph;
+    use glyphs_generator::lattice::Lattice;
+    use glyphs_generator::normalize::NormalizedGlyph;
+    use glyphs_generator::{
+        compute, compute_binary, compute_lattice, compute_normalized, decode_binary, initialize,
+        GlyphStepper,
+    };
     use std::fs;
 
     use relative_path::RelativePath;
@@ -14,6 +20,11 @@ mod tests {
         initialize(parameters_json);
     }
 
+    fn load_computable(name: &str) -> String {
+        let path = RelativePath::new("tests").join(name).to_string();
+        fs::read_to_string(path).expect("Failed to read computable file")
+    }
+
     #[test]
     fn test_computable_example() {
         setup();
@@ -25,4 +36,106 @@ mod tests {
         let result = compute(computable_json);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_computable_anchor_pairs() {
+        setup();
+        let computable_json = load_computable("computable.json");
+        let anchors_json = "{\"strokes\":[[0,1],[2,0],[2,3],[3,1],[1,2],[0,3]]}".to_string();
+
+        let from_strokes: Vec<Glyph> =
+            serde_json::from_str(&compute(computable_json).unwrap()).unwrap();
+        let from_anchors: Vec<Glyph> =
+            serde_json::from_str(&compute(anchors_json).unwrap()).unwrap();
+        assert_eq!(from_strokes, from_anchors);
+    }
+
+    #[test]
+    fn test_computable_lattice() {
+        setup();
+        let computable_json = load_computable("computable.json");
+
+        let glyphs: Vec<Glyph> =
+            serde_json::from_str(&compute(computable_json.clone()).unwrap()).unwrap();
+        let lattice: Lattice =
+            serde_json::from_str(&compute_lattice(computable_json).unwrap()).unwrap();
+        assert_eq!(lattice.nodes.len(), glyphs.len());
+        assert!(!lattice.edges.is_empty());
+    }
+
+    #[test]
+    fn test_computable_constraints() {
+        setup();
+        let strokes = "\"strokes\":[[0,1],[2,0],[2,3],[3,1],[1,2],[0,3]]";
+        let unconstrained: Vec<Glyph> =
+            serde_json::from_str(&compute(format!("{{{strokes}}}")).unwrap()).unwrap();
+        let constrained_json =
+            format!("{{{strokes},\"constraints\":{{\"forbidden_sets\":[[[1,2],[0,3]]]}}}}");
+        let constrained: Vec<Glyph> =
+            serde_json::from_str(&compute(constrained_json).unwrap()).unwrap();
+        assert!(!constrained.is_empty());
+        assert!(constrained.len() < unconstrained.len());
+        assert!(constrained.iter().all(|g| g.strokes.len() < 6));
+    }
+
+    #[test]
+    fn test_computable_binary() {
+        setup();
+        let computable_json = load_computable("computable.json");
+
+        let json = compute(computable_json.clone()).unwrap();
+        let glyphs: Vec<Glyph> = serde_json::from_str(&json).unwrap();
+        for compress in [false, true] {
+            let bytes = compute_binary(computable_json.clone(), compress).unwrap();
+            let decoded: Vec<Glyph> =
+                serde_json::from_str(&decode_binary(&bytes).unwrap()).unwrap();
+            assert_eq!(decoded, glyphs);
+        }
+    }
+
+    #[test]
+    fn test_computable_normalized() {
+        setup();
+        let computable_json = load_computable("computable.json");
+        let glyphs: Vec<Glyph> =
+            serde_json::from_str(&compute(computable_json.clone()).unwrap()).unwrap();
+
+        let computable: serde_json::Value = serde_json::from_str(&computable_json).unwrap();
+        let mut planar = computable.clone();
+        planar["normalization"] = "planar".into();
+        let normalized: Vec<NormalizedGlyph> =
+            serde_json::from_str(&compute_normalized(planar.to_string()).unwrap()).unwrap();
+        let kept: Vec<Glyph> = serde_json::from_str(&compute(planar.to_string()).unwrap()).unwrap();
+        assert_eq!(
+            normalized
+                .iter()
+                .map(|n| n.glyph.clone())
+                .collect::<Vec<_>>(),
+            kept
+        );
+        assert_eq!(
+            normalized
+                .iter()
+                .map(|n| 1 + n.collapsed.len())
+                .sum::<usize>(),
+            glyphs.len()
+        );
+    }
+
+    #[test]
+    fn test_computable_stepper() {
+        setup();
+        let computable_json = load_computable("computable.json");
+
+        let expected: Vec<Glyph> =
+            serde_json::from_str(&compute(computable_json.clone()).unwrap()).unwrap();
+        let mut stepper = GlyphStepper::new(computable_json).unwrap();
+        let mut glyphs: Vec<Glyph> = Vec::new();
+        while !stepper.done() {
+            let step: serde_json::Value =
+                serde_json::from_str(&stepper.step(0.0).unwrap()).unwrap();
+            glyphs.extend(serde_json::from_value::<Vec<Glyph>>(step["glyphs"].clone()).unwrap());
+        }
+        assert_eq!(glyphs, expected);
+    }
 }